@@ -0,0 +1,45 @@
+// Analyses the embedded word list at compile time so startup does not have to.
+// The analyzer source is shared with the crate, so the generated table always matches a runtime analysis.
+#[allow(dead_code)]
+#[path = "src/word_analyzer.rs"]
+mod word_analyzer;
+
+use std::fmt::Write;
+use word_analyzer::WordAnalyzer;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/words.txt");
+    println!("cargo:rerun-if-changed=src/word_analyzer.rs");
+
+    let word_list = std::fs::read_to_string("src/words.txt").expect("src/words.txt should exist");
+    let mut word_analyzer = WordAnalyzer::new();
+    for (idx, line) in word_list.lines().enumerate() {
+        // Invalid lines are left out of the table and reported as build warnings.
+        if let Err(e) = word_analyzer.analyze_word(line) {
+            println!(
+                "cargo:warning=src/words.txt line {}: skipped '{line}': {e}",
+                idx + 1
+            );
+        }
+    }
+    word_analyzer.finalize_probabilities();
+
+    let mut table = String::from("pub static EMBEDDED_WORDS: &[(&str, f64)] = &[\n");
+    for word in word_analyzer.words() {
+        writeln!(
+            table,
+            "    ({:?}, {:?}),",
+            word.as_str(),
+            word.total_probability
+        )
+        .unwrap();
+    }
+    table.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    std::fs::write(
+        std::path::Path::new(&out_dir).join("embedded_words.rs"),
+        table,
+    )
+    .expect("embedded word table should be writable");
+}
@@ -0,0 +1,119 @@
+use crate::word_list::{IssueKind, LineIssue, check_word, parse_date, parse_fields};
+use chrono::NaiveDate;
+
+/// An official puzzle from the local answer archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivedPuzzle {
+    pub number: u32,
+    pub date: Option<NaiveDate>,
+    pub answer: String,
+}
+
+/// How one version of crackle did on a set of archived puzzles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveRunSummary {
+    pub crackle_version: String,
+    pub runs: u32,
+    pub wins: u32,
+    /// Average number of guesses across won runs.
+    pub average_guesses: Option<f64>,
+}
+
+/// A local archive mapping puzzle numbers and dates to their answers.
+#[derive(Debug, Default, PartialEq)]
+pub struct Archive {
+    pub puzzles: Vec<ArchivedPuzzle>,
+}
+
+impl Archive {
+    /// Parses an archive file. Each line holds a puzzle number, an optional `YYYY-MM-DD` date and the answer,
+    /// separated by whitespace or commas, for example `0,2021-06-19,cigar`. Blank lines and `#` comments are skipped.
+    /// Puzzles are kept in puzzle number order and lines that cannot be read are reported.
+    pub fn parse(src: &str) -> (Self, Vec<LineIssue>) {
+        let (mut puzzles, issues) = parse_fields(src, |line, fields| {
+            let (number, date, answer) = match fields {
+                [number, answer] => (*number, None, *answer),
+                [number, date, answer] => (*number, Some(parse_date(date)?), *answer),
+                _ => {
+                    return Err((
+                        line.to_string(),
+                        IssueKind::Malformed("should be 'number,date,answer' or 'number,answer'"),
+                    ));
+                }
+            };
+            let number = number.parse::<u32>().map_err(|_| {
+                (
+                    number.to_string(),
+                    IssueKind::Malformed("is not a puzzle number"),
+                )
+            })?;
+            Ok(ArchivedPuzzle {
+                number,
+                date,
+                answer: check_word(answer)?,
+            })
+        });
+
+        puzzles.sort_by_key(|puzzle: &ArchivedPuzzle| puzzle.number);
+        (Archive { puzzles }, issues)
+    }
+
+    pub fn find_number(&self, number: u32) -> Option<&ArchivedPuzzle> {
+        self.puzzles.iter().find(|puzzle| puzzle.number == number)
+    }
+
+    /// Puzzles dated between `from` and `to`, inclusive. Undated puzzles are never included.
+    pub fn in_date_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<&ArchivedPuzzle> {
+        self.puzzles
+            .iter()
+            .filter(|puzzle| puzzle.date.is_some_and(|date| date >= from && date <= to))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARCHIVE: &str = "# number,date,answer\n1,2021-06-20,rebut\n0,2021-06-19,cigar\n2 SISSY\nx,2021-06-22,humph\n3,2021-06-22,hu\n";
+
+    #[test]
+    fn test_parse_archive() {
+        let (archive, issues) = Archive::parse(ARCHIVE);
+        let numbers: Vec<u32> = archive.puzzles.iter().map(|p| p.number).collect();
+        assert_eq!(numbers, vec![0, 1, 2]);
+        assert_eq!(archive.find_number(2).unwrap().answer, "sissy");
+        assert_eq!(archive.find_number(2).unwrap().date, None);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].line, 5);
+        assert_eq!(issues[1].line, 6);
+    }
+
+    #[test]
+    fn test_bad_date_is_reported() {
+        let (archive, issues) = Archive::parse("0,2021-06-19,cigar\n1,2021-06-31,rebut\n");
+        assert_eq!(archive.puzzles.len(), 1);
+        assert_eq!(
+            issues,
+            vec![LineIssue {
+                line: 2,
+                entry: "2021-06-31".to_string(),
+                kind: IssueKind::Malformed("is not a YYYY-MM-DD date"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_in_date_range() {
+        let (archive, _) = Archive::parse(ARCHIVE);
+        let date = |day| NaiveDate::from_ymd_opt(2021, 6, day).unwrap();
+
+        let answers: Vec<&str> = archive
+            .in_date_range(date(19), date(21))
+            .iter()
+            .map(|p| p.answer.as_str())
+            .collect();
+        assert_eq!(answers, vec!["cigar", "rebut"]);
+        assert!(archive.in_date_range(date(21), date(30)).is_empty());
+    }
+}
@@ -0,0 +1,688 @@
+use crate::DB;
+use crate::cli::BUILTIN_SOURCE;
+use crate::config::{Config, PastAnswerMode};
+use crate::constants::STRATEGY;
+use crate::game_engine::{GameEngine, score_guess};
+use crate::session::{SessionOutcome, pick_starting_word, play_turn};
+use crate::setup::{self, SetupError};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// Number of worst words listed when no other number is given.
+pub const WORST_WORDS: usize = 10;
+
+/// Rise in average guesses allowed before a comparison counts as a regression. Runs with the same seed and word list
+/// are deterministic, so by default any rise does.
+pub const DEFAULT_MAX_AVERAGE_INCREASE: f64 = 0.0;
+
+/// Extra failures allowed before a comparison counts as a regression.
+pub const DEFAULT_MAX_NEW_FAILURES: usize = 0;
+
+#[derive(Error, Debug)]
+pub enum BenchmarkError {
+    #[error("Database error: {0}")]
+    Rusqlite(#[from] rusqlite::Error),
+    #[error("Word source error: {0}")]
+    Setup(#[from] SetupError),
+    #[error("No words to benchmark")]
+    NoWords,
+}
+
+/// How the solver did against one target word.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordResult {
+    pub word: String,
+    /// `won`, `lost` when it ran out of guesses or `stumped` when no candidate was left.
+    pub outcome: SessionOutcome,
+    pub guesses: u8,
+}
+
+impl WordResult {
+    pub fn is_win(&self) -> bool {
+        self.outcome == SessionOutcome::Won
+    }
+
+    /// Orders results from best to worst: any win beats a failure, and fewer guesses are better. Failures all rank
+    /// the same, as the guesses they used say nothing about how close the solver was.
+    fn rank(&self) -> (bool, u8) {
+        (!self.is_win(), if self.is_win() { self.guesses } else { 0 })
+    }
+
+    /// `won in 4`, `lost after 6` or `stumped after 3`.
+    fn describe(&self) -> String {
+        if self.is_win() {
+            format!("won in {}", self.guesses)
+        } else {
+            format!("{} after {}", self.outcome, self.guesses)
+        }
+    }
+}
+
+/// One benchmark run: the setup it was played with and the result for every target word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkRun {
+    /// Row id in the results database, set once the run is stored.
+    pub id: Option<i64>,
+    pub created: chrono::DateTime<chrono::Utc>,
+    pub crackle_version: String,
+    pub strategy: String,
+    /// Name of the word source, or `builtin`.
+    pub word_source: String,
+    pub word_source_hash: String,
+    /// File the targets were read from. `None` when every word in the list was a target.
+    pub subset: Option<String>,
+    pub seed: u64,
+    pub opener: String,
+    pub max_guesses: u8,
+    pub results: Vec<WordResult>,
+}
+
+impl BenchmarkRun {
+    pub fn wins(&self) -> usize {
+        self.results.iter().filter(|result| result.is_win()).count()
+    }
+
+    /// Games that were lost or stumped, in target order.
+    pub fn failures(&self) -> Vec<&WordResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.is_win())
+            .collect()
+    }
+
+    /// Percentage of targets that were solved.
+    pub fn win_rate(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.wins() as f64 / self.results.len() as f64 * 100.0
+    }
+
+    /// Average number of guesses across won games.
+    pub fn average_guesses(&self) -> Option<f64> {
+        let wins = self.wins();
+        (wins > 0).then(|| {
+            self.results
+                .iter()
+                .filter(|result| result.is_win())
+                .map(|result| f64::from(result.guesses))
+                .sum::<f64>()
+                / wins as f64
+        })
+    }
+
+    /// Number of won games for each guess count.
+    pub fn distribution(&self) -> BTreeMap<u8, usize> {
+        let mut distribution = BTreeMap::new();
+        for result in self.results.iter().filter(|result| result.is_win()) {
+            *distribution.entry(result.guesses).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    /// The `limit` hardest targets: failures first, then the most guesses, then alphabetical.
+    pub fn worst(&self, limit: usize) -> Vec<&WordResult> {
+        let mut worst: Vec<&WordResult> = self.results.iter().collect();
+        worst.sort_by(|a, b| {
+            a.is_win()
+                .cmp(&b.is_win())
+                .then(b.guesses.cmp(&a.guesses))
+                .then(a.word.cmp(&b.word))
+        });
+        worst.truncate(limit);
+        worst
+    }
+
+    /// The summary written by `crackle --json benchmark`.
+    pub fn to_json(&self, worst: usize) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "created": self.created.to_rfc3339(),
+            "crackle_version": self.crackle_version,
+            "strategy": self.strategy,
+            "word_source": self.word_source,
+            "subset": self.subset,
+            "seed": self.seed,
+            "opener": self.opener,
+            "max_guesses": self.max_guesses,
+            "words": self.results.len(),
+            "wins": self.wins(),
+            "failures": self.failures(),
+            "win_rate": self.win_rate(),
+            "average_guesses": self.average_guesses(),
+            "distribution": self.distribution(),
+            "worst": self.worst(worst),
+        })
+    }
+}
+
+/// The engine after each turn and the outcome the turn led to, keyed by the feedback received so far. Every game
+/// starts from the same opener and the engine is deterministic, so games that received the same feedback always
+/// reach the same state.
+type TurnCache = HashMap<String, (GameEngine, Option<SessionOutcome>)>;
+
+/// Plays every target with one opener, picked from the seed the way a session picks it, and calls `progress` with
+/// the number of finished and total targets after each one. Nothing is stored.
+pub fn run(
+    targets: Vec<String>,
+    subset: Option<String>,
+    seed: u64,
+    config: &Config,
+    result_db: &DB,
+    word_db: &DB,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<BenchmarkRun, BenchmarkError> {
+    if targets.is_empty() {
+        return Err(BenchmarkError::NoWords);
+    }
+    let opener = pick_starting_word(
+        word_db,
+        config.get_limit(),
+        &mut StdRng::seed_from_u64(seed),
+    )?;
+    let mut engine = GameEngine::new();
+    if config.past_answer_mode != PastAnswerMode::Ignore {
+        engine.set_past_answers(
+            result_db.get_past_answers()?,
+            config.past_answer_mode,
+            config.past_answer_weight,
+        );
+    }
+    engine.set_starting_word(opener.clone());
+    log::info!(
+        "benchmark start words={} seed={seed} opener={opener}",
+        targets.len()
+    );
+
+    let mut cache = TurnCache::new();
+    let mut results = Vec::with_capacity(targets.len());
+    for target in &targets {
+        results.push(solve(
+            target,
+            engine.clone(),
+            word_db,
+            config.get_max_guesses(),
+            &mut cache,
+        )?);
+        progress(results.len(), targets.len());
+    }
+
+    let run = BenchmarkRun {
+        id: None,
+        created: chrono::Utc::now(),
+        crackle_version: env!("CARGO_PKG_VERSION").to_string(),
+        strategy: STRATEGY.to_string(),
+        word_source: config
+            .active_word_source
+            .clone()
+            .unwrap_or_else(|| BUILTIN_SOURCE.to_string()),
+        word_source_hash: setup::active_word_source_hash(config, result_db)?,
+        subset,
+        seed,
+        opener,
+        max_guesses: config.get_max_guesses(),
+        results,
+    };
+    log::info!(
+        "benchmark end words={} wins={} average={:?}",
+        run.results.len(),
+        run.wins(),
+        run.average_guesses()
+    );
+    Ok(run)
+}
+
+/// Plays one game against `target` with feedback scored from it, taking turns with `play_turn` as a session does.
+fn solve(
+    target: &str,
+    mut engine: GameEngine,
+    word_db: &DB,
+    max_guesses: u8,
+    cache: &mut TurnCache,
+) -> Result<WordResult, rusqlite::Error> {
+    let mut path = String::new();
+    let mut guesses = 0;
+    loop {
+        guesses += 1;
+        let feedback = score_guess(engine.get_current_guess(), target)
+            .expect("targets and guesses both come from the word list");
+        path.push_str(&feedback);
+        let outcome;
+        (engine, outcome) = match cache.get(&path) {
+            Some(turn) => turn.clone(),
+            None => {
+                let outcome =
+                    play_turn(&mut engine, word_db, &feedback, guesses, max_guesses)?.outcome();
+                cache.insert(path.clone(), (engine.clone(), outcome));
+                (engine, outcome)
+            }
+        };
+        if let Some(outcome) = outcome {
+            return Ok(WordResult {
+                word: target.to_string(),
+                outcome,
+                guesses,
+            });
+        }
+    }
+}
+
+/// Renders a run as text, listing every failure and the `worst` hardest words.
+pub fn render(run: &BenchmarkRun, worst: usize) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Benchmark of {} words from {}{} with opener '{}' (seed {})",
+        run.results.len(),
+        run.word_source,
+        run.subset
+            .as_deref()
+            .map_or(String::new(), |subset| format!(", subset {subset}")),
+        run.opener,
+        run.seed
+    );
+    let _ = writeln!(
+        out,
+        "  Wins: {}  Failures: {}  Win rate: {:.1}%",
+        run.wins(),
+        run.results.len() - run.wins(),
+        run.win_rate()
+    );
+    let _ = writeln!(
+        out,
+        "  Average guesses (wins): {}",
+        run.average_guesses()
+            .map_or("-".to_string(), |avg| format!("{avg:.3}"))
+    );
+    let _ = writeln!(out, "  Guess distribution:");
+    for (guesses, count) in run.distribution() {
+        let _ = writeln!(out, "    {guesses}: {count}");
+    }
+    let _ = writeln!(out, "    X: {}", run.results.len() - run.wins());
+
+    let failures = run.failures();
+    if !failures.is_empty() {
+        let _ = writeln!(out, "  Failures:");
+        for failure in failures {
+            let _ = writeln!(
+                out,
+                "    {} {} after {} guesses",
+                failure.word, failure.outcome, failure.guesses
+            );
+        }
+    }
+    let _ = writeln!(out, "  Worst words:");
+    for result in run.worst(worst) {
+        let _ = writeln!(
+            out,
+            "    {} {} in {}",
+            result.word, result.outcome, result.guesses
+        );
+    }
+    out
+}
+
+/// A word whose result changed between the baseline and the new run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordChange {
+    pub word: String,
+    pub baseline: WordResult,
+    pub current: WordResult,
+}
+
+/// How far a comparison may regress before it fails.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub max_average_increase: f64,
+    pub max_new_failures: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            max_average_increase: DEFAULT_MAX_AVERAGE_INCREASE,
+            max_new_failures: DEFAULT_MAX_NEW_FAILURES,
+        }
+    }
+}
+
+/// A new run compared with a saved baseline over the words both runs played.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub baseline: String,
+    pub baseline_run: Option<i64>,
+    /// Differences in setup, such as another opener, that make changes expected.
+    pub warnings: Vec<String>,
+    /// Words played by both runs.
+    pub words: usize,
+    pub baseline_average: Option<f64>,
+    pub average: Option<f64>,
+    pub baseline_failures: usize,
+    pub failures: usize,
+    pub better: Vec<WordChange>,
+    pub worse: Vec<WordChange>,
+    /// Thresholds that were passed. Empty when the run is no worse than allowed.
+    pub regressions: Vec<String>,
+}
+
+impl Comparison {
+    /// Change in average guesses of won games. `None` when either run won nothing.
+    pub fn average_change(&self) -> Option<f64> {
+        Some(self.average? - self.baseline_average?)
+    }
+
+    pub fn regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!(self);
+        value["average_change"] = serde_json::json!(self.average_change());
+        value["regressed"] = serde_json::json!(self.regressed());
+        value
+    }
+}
+
+/// Compares `run` with the `baseline` run saved under `name`, word by word, and checks the change against `thresholds`.
+/// Only words played by both runs count, so a subset run can be compared with a baseline of the whole list.
+pub fn compare(
+    name: &str,
+    baseline: &BenchmarkRun,
+    run: &BenchmarkRun,
+    thresholds: Thresholds,
+) -> Comparison {
+    let mut warnings = Vec::new();
+    if baseline.opener != run.opener {
+        warnings.push(format!(
+            "The baseline opened with '{}', this run with '{}'",
+            baseline.opener, run.opener
+        ));
+    }
+    if baseline.word_source_hash != run.word_source_hash {
+        warnings.push(format!(
+            "The baseline used a different '{}' word list",
+            baseline.word_source
+        ));
+    }
+    if baseline.max_guesses != run.max_guesses {
+        warnings.push(format!(
+            "The baseline allowed {} guesses, this run {}",
+            baseline.max_guesses, run.max_guesses
+        ));
+    }
+
+    let previous: HashMap<&str, &WordResult> = baseline
+        .results
+        .iter()
+        .map(|result| (result.word.as_str(), result))
+        .collect();
+    let mut pairs = Vec::new();
+    for current in &run.results {
+        if let Some(before) = previous.get(current.word.as_str()) {
+            pairs.push((*before, current));
+        }
+    }
+    let unmatched = baseline.results.len() + run.results.len() - 2 * pairs.len();
+    if unmatched > 0 {
+        warnings.push(format!(
+            "{unmatched} words were only played by one of the runs and are left out"
+        ));
+    }
+
+    let average = |results: &[&WordResult]| {
+        let wins: Vec<f64> = results
+            .iter()
+            .filter(|result| result.is_win())
+            .map(|result| f64::from(result.guesses))
+            .collect();
+        (!wins.is_empty()).then(|| wins.iter().sum::<f64>() / wins.len() as f64)
+    };
+    let before: Vec<&WordResult> = pairs.iter().map(|(before, _)| *before).collect();
+    let after: Vec<&WordResult> = pairs.iter().map(|(_, after)| *after).collect();
+    let failures = |results: &[&WordResult]| results.iter().filter(|r| !r.is_win()).count();
+
+    let mut better = Vec::new();
+    let mut worse = Vec::new();
+    for (before, after) in &pairs {
+        let change = || WordChange {
+            word: after.word.clone(),
+            baseline: (*before).clone(),
+            current: (*after).clone(),
+        };
+        match after.rank().cmp(&before.rank()) {
+            std::cmp::Ordering::Less => better.push(change()),
+            std::cmp::Ordering::Greater => worse.push(change()),
+            std::cmp::Ordering::Equal => {}
+        }
+    }
+
+    let mut comparison = Comparison {
+        baseline: name.to_string(),
+        baseline_run: baseline.id,
+        warnings,
+        words: pairs.len(),
+        baseline_average: average(&before),
+        average: average(&after),
+        baseline_failures: failures(&before),
+        failures: failures(&after),
+        better,
+        worse,
+        regressions: Vec::new(),
+    };
+    if let Some(change) = comparison.average_change()
+        && change > thresholds.max_average_increase
+    {
+        comparison.regressions.push(format!(
+            "Average guesses rose by {change:.3}, more than the allowed {:.3}",
+            thresholds.max_average_increase
+        ));
+    }
+    let new_failures = comparison
+        .failures
+        .saturating_sub(comparison.baseline_failures);
+    if new_failures > thresholds.max_new_failures {
+        comparison.regressions.push(format!(
+            "Failures rose by {new_failures}, more than the allowed {}",
+            thresholds.max_new_failures
+        ));
+    }
+    comparison
+}
+
+/// Renders a comparison as text, listing every word that got better or worse.
+pub fn render_comparison(comparison: &Comparison) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Compared with baseline '{}'{} over {} words",
+        comparison.baseline,
+        comparison
+            .baseline_run
+            .map_or(String::new(), |id| format!(" (run #{id})")),
+        comparison.words
+    );
+    for warning in &comparison.warnings {
+        let _ = writeln!(out, "  Warning: {warning}");
+    }
+    let average = |avg: Option<f64>| avg.map_or("-".to_string(), |avg| format!("{avg:.3}"));
+    let _ = writeln!(
+        out,
+        "  Average guesses (wins): {} -> {} ({})",
+        average(comparison.baseline_average),
+        average(comparison.average),
+        comparison
+            .average_change()
+            .map_or("-".to_string(), |change| format!("{change:+.3}"))
+    );
+    let _ = writeln!(
+        out,
+        "  Failures: {} -> {}",
+        comparison.baseline_failures, comparison.failures
+    );
+    for (label, changes) in [("Better", &comparison.better), ("Worse", &comparison.worse)] {
+        if changes.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "  {label} ({}):", changes.len());
+        for change in changes {
+            let _ = writeln!(
+                out,
+                "    {} {} -> {}",
+                change.word,
+                change.baseline.describe(),
+                change.current.describe()
+            );
+        }
+    }
+    if comparison.regressed() {
+        for regression in &comparison.regressions {
+            let _ = writeln!(out, "Regression: {regression}");
+        }
+    } else {
+        let _ = writeln!(out, "No regression past the thresholds");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: &str = "crane\nslate\nplate\ngrate\nirate\nfight\n";
+
+    fn targets(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    fn result(word: &str, outcome: SessionOutcome, guesses: u8) -> WordResult {
+        WordResult {
+            word: word.to_string(),
+            outcome,
+            guesses,
+        }
+    }
+
+    #[test]
+    fn test_run_plays_every_target() {
+        let config = Config {
+            starting_word_limit: 1,
+            ..Config::default()
+        };
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        let words = targets(&["crane", "slate", "plate", "grate", "irate", "fight"]);
+        let mut seen = Vec::new();
+
+        let run = run(
+            words.clone(),
+            None,
+            5,
+            &config,
+            &result_db,
+            &word_db,
+            |done, total| seen.push((done, total)),
+        )
+        .unwrap();
+        assert_eq!(seen.last(), Some(&(6, 6)));
+        assert_eq!(
+            run.results.iter().map(|r| &r.word).collect::<Vec<_>>(),
+            words.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(run.word_source, BUILTIN_SOURCE);
+        let opener = run.results.iter().find(|r| r.guesses == 1).unwrap();
+        assert_eq!(opener.word, run.opener);
+        assert!(run.results.iter().all(|r| r.guesses <= config.max_guesses));
+
+        assert!(matches!(
+            super::run(
+                Vec::new(),
+                None,
+                5,
+                &config,
+                &result_db,
+                &word_db,
+                |_, _| {}
+            ),
+            Err(BenchmarkError::NoWords)
+        ));
+    }
+
+    fn sample_run(results: Vec<WordResult>) -> BenchmarkRun {
+        BenchmarkRun {
+            id: None,
+            created: chrono::Utc::now(),
+            crackle_version: env!("CARGO_PKG_VERSION").to_string(),
+            strategy: STRATEGY.to_string(),
+            word_source: BUILTIN_SOURCE.to_string(),
+            word_source_hash: "hash".to_string(),
+            subset: None,
+            seed: 1,
+            opener: "crane".to_string(),
+            max_guesses: 6,
+            results,
+        }
+    }
+
+    #[test]
+    fn test_statistics_and_worst_words() {
+        let run = sample_run(vec![
+            result("crane", SessionOutcome::Won, 1),
+            result("slate", SessionOutcome::Won, 4),
+            result("plate", SessionOutcome::Won, 5),
+            result("grate", SessionOutcome::Lost, 6),
+            result("irate", SessionOutcome::Stumped, 3),
+        ]);
+        assert_eq!(run.wins(), 3);
+        assert_eq!(run.failures().len(), 2);
+        assert_eq!(run.win_rate(), 60.0);
+        assert_eq!(run.average_guesses(), Some(10.0 / 3.0));
+        assert_eq!(run.distribution(), BTreeMap::from([(1, 1), (4, 1), (5, 1)]));
+        let worst: Vec<&str> = run.worst(3).iter().map(|r| r.word.as_str()).collect();
+        assert_eq!(worst, vec!["grate", "irate", "plate"]);
+        assert!(render(&run, 3).contains("grate lost after 6 guesses"));
+    }
+
+    #[test]
+    fn test_compare_reports_changes_and_regressions() {
+        let baseline = sample_run(vec![
+            result("crane", SessionOutcome::Won, 1),
+            result("slate", SessionOutcome::Won, 4),
+            result("grate", SessionOutcome::Lost, 6),
+            result("fight", SessionOutcome::Won, 3),
+            result("irate", SessionOutcome::Lost, 6),
+        ]);
+        let mut run = sample_run(vec![
+            result("crane", SessionOutcome::Won, 1),
+            result("slate", SessionOutcome::Won, 5),
+            result("grate", SessionOutcome::Won, 6),
+            result("irate", SessionOutcome::Stumped, 2),
+        ]);
+        run.opener = "slate".to_string();
+
+        let comparison = compare("main", &baseline, &run, Thresholds::default());
+        assert_eq!(comparison.words, 4);
+        assert_eq!(comparison.warnings.len(), 2);
+        assert_eq!(comparison.better.len(), 1);
+        assert_eq!(comparison.better[0].word, "grate");
+        assert_eq!(comparison.worse.len(), 1);
+        assert_eq!(comparison.worse[0].word, "slate");
+        assert_eq!(comparison.baseline_failures, 2);
+        assert_eq!(comparison.failures, 1);
+        assert_eq!(comparison.average_change(), Some(4.0 - 2.5));
+        assert_eq!(comparison.regressions.len(), 1);
+        assert!(render_comparison(&comparison).contains("grate lost after 6 -> won in 6"));
+
+        let loose = Thresholds {
+            max_average_increase: 2.0,
+            max_new_failures: 0,
+        };
+        assert!(!compare("main", &baseline, &run, loose).regressed());
+        let failing = compare("main", &run, &baseline, loose);
+        assert_eq!(failing.regressions.len(), 1);
+        assert!(failing.regressions[0].contains("Failures rose by 1"));
+    }
+}
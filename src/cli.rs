@@ -0,0 +1,389 @@
+use crate::benchmark::{
+    DEFAULT_MAX_AVERAGE_INCREASE, DEFAULT_MAX_NEW_FAILURES, Thresholds, WORST_WORDS,
+};
+use crate::config::{Config, PastAnswerMode};
+use crate::server::DEFAULT_ADDR;
+use crate::session::{MAX_SEED, SessionOutcome};
+use crate::suggest::DEFAULT_CANDIDATE_LIMIT;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Name accepted by `--word-source` and `sources use` for the built-in word list.
+pub const BUILTIN_SOURCE: &str = "builtin";
+
+/// Command line interface. Running without a subcommand opens the interactive menu.
+#[derive(Parser, Debug)]
+#[command(name = "crackle", version, about = "A probabilistic Wordle solver")]
+pub struct Cli {
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+
+    /// Print results and errors as JSON on stdout instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Flags that override `Config` fields for a single run. They are never written back to `config.toml`.
+#[derive(Args, Debug, Default, Clone)]
+pub struct ConfigOverrides {
+    /// Results database to use instead of the configured one
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+
+    /// Number of top words the starting guess is picked from
+    #[arg(long, global = true, value_name = "N")]
+    pub starting_word_limit: Option<u8>,
+
+    /// Guesses allowed per game
+    #[arg(long, global = true, value_name = "N")]
+    pub max_guesses: Option<u8>,
+
+    /// Registered word source to solve with, or `builtin` for the built-in list
+    #[arg(long, global = true, value_name = "NAME")]
+    pub word_source: Option<String>,
+
+    /// How previously used official answers are treated
+    #[arg(long, global = true, value_enum, value_name = "MODE")]
+    pub past_answers: Option<PastAnswerArg>,
+
+    /// Profile that sessions are recorded under
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Seed for the opener and test targets, so a run can be reproduced
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(..=MAX_SEED))]
+    pub seed: Option<u64>,
+}
+
+impl ConfigOverrides {
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(db) = &self.db {
+            config.app_db = db.clone();
+        }
+        if let Some(limit) = self.starting_word_limit {
+            config.starting_word_limit = limit;
+        }
+        if let Some(max_guesses) = self.max_guesses {
+            config.max_guesses = max_guesses;
+        }
+        if let Some(source) = &self.word_source {
+            config.active_word_source = (source != BUILTIN_SOURCE).then(|| source.clone());
+        }
+        if let Some(mode) = self.past_answers {
+            config.past_answer_mode = mode.into();
+        }
+        if let Some(profile) = &self.profile {
+            config.active_profile = Some(profile.clone());
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+    }
+}
+
+/// Command line spelling of `PastAnswerMode`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum PastAnswerArg {
+    Ignore,
+    Drop,
+    DownWeight,
+}
+
+impl From<PastAnswerArg> for PastAnswerMode {
+    fn from(arg: PastAnswerArg) -> Self {
+        match arg {
+            PastAnswerArg::Ignore => PastAnswerMode::Ignore,
+            PastAnswerArg::Drop => PastAnswerMode::Drop,
+            PastAnswerArg::DownWeight => PastAnswerMode::DownWeight,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Play one interactive game, reading feedback from stdin
+    Play,
+    /// Play one game on a full-screen board, colouring tiles with the keyboard
+    Tui,
+    /// Let the solver play against random words from the active word list
+    Test {
+        /// Number of games to play, defaults to `test_runs` from the config
+        #[arg(long, short)]
+        runs: Option<u8>,
+    },
+    /// Print statistics for the stored sessions
+    Report {
+        /// Only include sessions started on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// Only include sessions started on or before this date (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// Only include one session type, such as Interactive or Test
+        #[arg(long)]
+        session_type: Option<String>,
+        /// Rank every profile instead of reporting on the active one
+        #[arg(long)]
+        leaderboard: bool,
+        /// Write a plain text copy of the report to this file
+        #[arg(long, short, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Print the next guess and remaining candidates for a game played elsewhere, without storing anything
+    Suggest {
+        /// Guesses so far as guess:feedback pairs, e.g. `crane:nnygy slate:ngnny`. Read from stdin when omitted and stdin is not a terminal
+        history: Vec<String>,
+        /// Number of remaining candidates to list
+        #[arg(long, default_value_t = DEFAULT_CANDIDATE_LIMIT)]
+        limit: usize,
+    },
+    /// Re-run a replay file written for a stumped game and show where the engine diverges from it
+    Replay {
+        /// Replay file, found in the `replays` directory next to the results database
+        file: PathBuf,
+    },
+    /// Let the solver play every word in the active list, or the words in a file, and store the summary
+    Benchmark(BenchmarkArgs),
+    /// Play games driven by JSON-lines messages on stdin and stdout
+    Api,
+    /// Serve the solver as a JSON API over HTTP, keeping the word list loaded between requests
+    Serve {
+        /// Address to listen on. Use 0.0.0.0 only on a trusted network, as there is no authentication
+        #[arg(long, default_value = DEFAULT_ADDR)]
+        addr: String,
+    },
+    /// Manage registered word sources
+    Sources {
+        #[command(subcommand)]
+        action: SourcesAction,
+    },
+    /// Show or change settings in config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+impl Command {
+    /// Whether the command stores sessions under the active profile, so the profile has to be registered first.
+    pub fn records_sessions(&self) -> bool {
+        matches!(
+            self,
+            Command::Play
+                | Command::Tui
+                | Command::Test { .. }
+                | Command::Api
+                | Command::Serve { .. }
+        )
+    }
+}
+
+/// Options of `crackle benchmark`.
+#[derive(Args, Debug)]
+pub struct BenchmarkArgs {
+    /// File of target words, one per line, instead of the whole active list
+    #[arg(long, value_name = "PATH")]
+    pub words: Option<PathBuf>,
+    /// Number of hardest words to list
+    #[arg(long, default_value_t = WORST_WORDS)]
+    pub worst: usize,
+    /// Compare the run with a saved baseline and exit with status 7 if it regressed. Uses the baseline's seed unless `--seed` is given
+    #[arg(long, value_name = "NAME")]
+    pub compare: Option<String>,
+    /// Save the run as a named baseline, replacing any baseline with that name
+    #[arg(long, value_name = "NAME")]
+    pub save_baseline: Option<String>,
+    /// Rise in average guesses allowed by `--compare`
+    #[arg(long, value_name = "GUESSES", default_value_t = DEFAULT_MAX_AVERAGE_INCREASE)]
+    pub max_average_increase: f64,
+    /// Extra failures allowed by `--compare`
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_NEW_FAILURES)]
+    pub max_new_failures: usize,
+}
+
+impl BenchmarkArgs {
+    pub fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            max_average_increase: self.max_average_increase,
+            max_new_failures: self.max_new_failures,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SourcesAction {
+    /// List registered word sources
+    List,
+    /// Register a word list file under a name
+    Add { name: String, path: PathBuf },
+    /// Switch the active word source, or back to `builtin`
+    Use { name: String },
+    /// Remove a registered word source
+    Remove { name: String },
+    /// Check a word list file and report problem lines
+    Validate {
+        path: PathBuf,
+        /// Write the cleaned list to this file
+        #[arg(long, value_name = "PATH")]
+        write_cleaned: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the settings in effect, including command line overrides
+    Show,
+    /// Print the path of config.toml
+    Path,
+    /// Set a key in config.toml
+    Set { key: String, value: String },
+    /// Remove a key from config.toml so it falls back to its default
+    Unset { key: String },
+}
+
+/// Exit status of a command. clap itself exits with `Usage` when the arguments cannot be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success = 0,
+    /// An error stopped the command.
+    Failure = 1,
+    Usage = 2,
+    /// A game was lost or the solver got stuck.
+    Lost = 3,
+    /// A named word source, file or key does not exist.
+    NotFound = 4,
+    /// The input had problems, such as a word list with bad lines.
+    Invalid = 5,
+    /// The player left a game before it ended.
+    Abandoned = 6,
+    /// A benchmark did worse than its baseline by more than the allowed threshold.
+    Regressed = 7,
+}
+
+impl Status {
+    /// Name used for the status in `--json` error output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Success => "success",
+            Status::Failure => "failure",
+            Status::Usage => "usage",
+            Status::Lost => "lost",
+            Status::NotFound => "not_found",
+            Status::Invalid => "invalid",
+            Status::Abandoned => "abandoned",
+            Status::Regressed => "regressed",
+        }
+    }
+}
+
+impl From<SessionOutcome> for Status {
+    fn from(outcome: SessionOutcome) -> Self {
+        match outcome {
+            SessionOutcome::Won => Status::Success,
+            SessionOutcome::Lost | SessionOutcome::Stumped => Status::Lost,
+            SessionOutcome::Abandoned => Status::Abandoned,
+        }
+    }
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_subcommand_opens_menu() {
+        let cli = Cli::try_parse_from(["crackle"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_only_game_commands_record_sessions() {
+        let records = |args: &[&str]| {
+            Cli::try_parse_from(args)
+                .unwrap()
+                .command
+                .unwrap()
+                .records_sessions()
+        };
+        assert!(records(&["crackle", "play"]));
+        assert!(records(&["crackle", "test"]));
+        assert!(!records(&["crackle", "report"]));
+        assert!(!records(&["crackle", "suggest", "crane:nnnnn"]));
+    }
+
+    #[test]
+    fn test_parse_test_runs() {
+        let cli = Cli::try_parse_from(["crackle", "test", "--runs", "25"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Test { runs: Some(25) })
+        ));
+        assert!(Cli::try_parse_from(["crackle", "test", "--runs", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_suggest_history() {
+        let cli =
+            Cli::try_parse_from(["crackle", "suggest", "crane:nnygy", "slate:ngnny"]).unwrap();
+        let Some(Command::Suggest { history, limit }) = cli.command else {
+            panic!("expected suggest");
+        };
+        assert_eq!(history, vec!["crane:nnygy", "slate:ngnny"]);
+        assert_eq!(limit, 20);
+    }
+
+    #[test]
+    fn test_json_flag_is_global() {
+        let cli = Cli::try_parse_from(["crackle", "suggest", "--json", "crane:nnygy"]).unwrap();
+        assert!(cli.json);
+        assert!(!Cli::try_parse_from(["crackle", "report"]).unwrap().json);
+    }
+
+    #[test]
+    fn test_overrides_apply_to_config() {
+        let cli = Cli::try_parse_from([
+            "crackle",
+            "report",
+            "--max-guesses",
+            "8",
+            "--word-source",
+            "builtin",
+            "--past-answers",
+            "down-weight",
+            "--profile",
+            "sam",
+            "--seed",
+            "7",
+        ])
+        .unwrap();
+        let mut config = Config {
+            active_word_source: Some("mine".to_string()),
+            ..Config::default()
+        };
+        cli.overrides.apply(&mut config);
+
+        assert_eq!(config.max_guesses, 8);
+        assert_eq!(config.active_word_source, None);
+        assert_eq!(config.past_answer_mode, PastAnswerMode::DownWeight);
+        assert_eq!(config.profile(), "sam");
+        assert_eq!(config.seed, Some(7));
+        assert_eq!(
+            config.starting_word_limit,
+            Config::default().starting_word_limit
+        );
+
+        let too_large = (MAX_SEED + 1).to_string();
+        assert!(Cli::try_parse_from(["crackle", "--seed", too_large.as_str(), "test"]).is_err());
+    }
+}
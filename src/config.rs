@@ -1,21 +1,119 @@
 use dirs;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use thiserror::Error;
 use toml;
 
-#[derive(Deserialize, Serialize, Debug)]
+/// Profile used when none has been picked, and for sessions recorded before profiles existed.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Keys that can be changed with `Config::set_value`. `app_db` is always placed next to `config.toml` so it is left out.
+pub const SETTABLE_KEYS: &[&str] = &[
+    "starting_word_limit",
+    "max_guesses",
+    "test_runs",
+    "active_word_source",
+    "past_answer_mode",
+    "past_answer_weight",
+    "answer_archive",
+    "active_profile",
+    "log_level",
+    "seed",
+    "allowed_origins",
+];
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Unknown config key '{0}'")]
+    UnknownKey(String),
+
+    #[error("Invalid value for '{key}': {message}")]
+    InvalidValue { key: String, message: String },
+}
+
+/// How previously used official answers are treated when picking the next guess.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PastAnswerMode {
+    /// Past answers are ranked like any other candidate.
+    #[default]
+    Ignore,
+    /// Past answers are only suggested when no other candidate is left.
+    Drop,
+    /// Past answers have their probability multiplied by `past_answer_weight`.
+    DownWeight,
+}
+
+/// How much is written to `crackle.log`. The `CRACKLE_LOG` environment variable takes precedence.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    /// One line per turn plus the start and end of every session.
+    #[default]
+    Info,
+    /// Also dumps the engine state when the solver is stumped.
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(self) -> log::LevelFilter {
+        match self {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            other => Err(format!("Unknown log level '{other}'")),
+        }
+    }
+}
+
+// Missing fields fall back to their defaults so config files written by older versions keep loading.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
 pub struct Config {
-    pub word_list_path: String,
     pub starting_word_limit: u8,
     pub app_db: PathBuf,
     pub max_guesses: u8,
     pub test_runs: u8,
+    /// Name of the registered word source to solve with. `None` uses the built-in list.
+    pub active_word_source: Option<String>,
+    pub past_answer_mode: PastAnswerMode,
+    pub past_answer_weight: f64,
+    /// Answer archive used to replay past puzzles. `None` uses `answer_archive.txt` in the config directory.
+    pub answer_archive: Option<PathBuf>,
+    /// Profile that sessions are recorded under. `None` uses the default profile.
+    pub active_profile: Option<String>,
+    pub log_level: LogLevel,
+    /// Seed for the opener and test targets, so runs can be reproduced. `None` picks a new seed every run.
+    pub seed: Option<u64>,
+    /// Web origins, such as `http://localhost:3000`, whose pages may call `crackle serve`. Empty allows none.
+    pub allowed_origins: Vec<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
-            word_list_path: "words.txt".to_string(),
             starting_word_limit: 10,
             app_db: dirs::config_dir()
                 .unwrap()
@@ -23,21 +121,23 @@ impl Default for Config {
                 .join("crackle.db"),
             max_guesses: 6,
             test_runs: 10,
+            active_word_source: None,
+            past_answer_mode: PastAnswerMode::Ignore,
+            past_answer_weight: 0.5,
+            answer_archive: None,
+            active_profile: None,
+            log_level: LogLevel::Info,
+            seed: None,
+            allowed_origins: Vec::new(),
         }
     }
 }
 
 impl Config {
-    pub fn new(word_list_path: String, starting_word_limit: u8) -> Self {
+    pub fn new(starting_word_limit: u8) -> Self {
         Config {
-            word_list_path,
             starting_word_limit,
-            app_db: dirs::config_dir()
-                .unwrap()
-                .join("crackle")
-                .join("crackle.db"),
-            max_guesses: 6,
-            test_runs: 10,
+            ..Config::default()
         }
     }
 
@@ -45,6 +145,74 @@ impl Config {
         self.app_db = app_db;
     }
 
+    /// Writes the config back to `config.toml` so changes made from the menu persist.
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let config_str = toml::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(config_path(), config_str)
+    }
+
+    /// Sets `key` from its TOML spelling, or resets it to the default when `value` is `None`.
+    /// Values that are not valid TOML, such as a bare word, are read as strings.
+    pub fn set_value(&mut self, key: &str, value: Option<&str>) -> Result<(), ConfigError> {
+        if !SETTABLE_KEYS.contains(&key) {
+            return Err(ConfigError::UnknownKey(key.to_string()));
+        }
+        let invalid = |message: String| ConfigError::InvalidValue {
+            key: key.to_string(),
+            message,
+        };
+
+        let mut table = toml::Table::try_from(&*self).map_err(|e| invalid(e.to_string()))?;
+        match value {
+            Some(value) => {
+                let parsed = toml::from_str::<toml::Table>(&format!("value = {value}"))
+                    .ok()
+                    .and_then(|mut parsed| parsed.remove("value"))
+                    .unwrap_or_else(|| toml::Value::String(value.to_string()));
+                table.insert(key.to_string(), parsed);
+            }
+            None => {
+                table.remove(key);
+            }
+        }
+        let config: Config = table.try_into().map_err(|e| invalid(e.to_string()))?;
+        check_past_answer_weight(&config.past_answer_weight).map_err(invalid)?;
+        *self = config;
+        Ok(())
+    }
+
+    /// Path of the persistent custom word list, kept next to the results database.
+    pub fn custom_words_path(&self) -> PathBuf {
+        self.app_db.with_file_name("custom_words.txt")
+    }
+
+    /// Name of the active profile.
+    pub fn profile(&self) -> &str {
+        self.active_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Path of the answer archive, falling back to `answer_archive.txt` next to the results database.
+    pub fn answer_archive_path(&self) -> PathBuf {
+        self.answer_archive
+            .clone()
+            .unwrap_or_else(|| self.app_db.with_file_name("answer_archive.txt"))
+    }
+
+    /// Path of the on-disk cache of the analysed word database.
+    pub fn word_cache_path(&self) -> PathBuf {
+        self.app_db.with_file_name("word_cache.db")
+    }
+
+    /// Directory replay files of stumped games are written to.
+    pub fn replay_dir(&self) -> PathBuf {
+        self.app_db.with_file_name("replays")
+    }
+
+    /// Path of the log file, kept next to the results database. Rotated files get a `.1`, `.2`, ... suffix.
+    pub fn log_path(&self) -> PathBuf {
+        self.app_db.with_file_name("crackle.log")
+    }
+
     //TODO: maybe change to smaller size for preformance
     pub fn get_limit(&self) -> u8 {
         self.starting_word_limit
@@ -56,18 +224,49 @@ impl Config {
     }
 }
 
-pub fn get_config() -> Config {
+/// Checks that a past answer weight is between 0.0 and 1.0, so down-weighting never boosts past answers.
+pub fn check_past_answer_weight(weight: &f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(weight) {
+        Ok(())
+    } else {
+        Err(format!("{weight} is not between 0.0 and 1.0"))
+    }
+}
+
+/// Path of `config.toml` in the platform configuration directory.
+pub fn config_path() -> PathBuf {
     let mut config_path = dirs::config_dir()
         .ok_or("Could not find config directory")
         .unwrap();
 
     config_path.push("crackle");
     config_path.push("config.toml");
+    config_path
+}
+
+pub fn get_config() -> Config {
+    load_config(true)
+}
+
+/// Reads `config.toml` like `get_config`, but falls back to the defaults instead of creating the file.
+pub fn read_config() -> Config {
+    load_config(false)
+}
+
+fn load_config(create: bool) -> Config {
+    let config_path = config_path();
 
     let mut config = match std::fs::read_to_string(config_path.clone()) {
-        Ok(content) => toml::from_str(&content).unwrap_or_else(|_| create_config(&config_path)),
-        Err(_) => create_config(&config_path),
-    };
+        Ok(content) => toml::from_str(&content).ok(),
+        Err(_) => None,
+    }
+    .unwrap_or_else(|| {
+        if create {
+            create_config(&config_path)
+        } else {
+            Config::default()
+        }
+    });
     let expected_db_path = config_path.parent().unwrap().join("crackle.db");
     if config.app_db != expected_db_path {
         config.update_app_db(expected_db_path);
@@ -80,8 +279,8 @@ fn create_config(config_path: &PathBuf) -> Config {
         std::fs::create_dir_all(parent).unwrap();
     }
 
-    println!("Created config file at: {}", config_path.display());
-    println!("You can edit to customize your settings.");
+    eprintln!("Created config file at: {}", config_path.display());
+    eprintln!("You can edit to customize your settings.");
 
     let mut config = Config::default();
 
@@ -97,3 +296,60 @@ fn create_config(config_path: &PathBuf) -> Config {
     std::fs::write(config_path, config_str).unwrap();
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_value() {
+        let mut config = Config::default();
+        config.set_value("max_guesses", Some("8")).unwrap();
+        config.set_value("past_answer_mode", Some("drop")).unwrap();
+        config.set_value("active_profile", Some("sam")).unwrap();
+        assert_eq!(config.max_guesses, 8);
+        assert_eq!(config.past_answer_mode, PastAnswerMode::Drop);
+        assert_eq!(config.profile(), "sam");
+
+        config.set_value("active_profile", None).unwrap();
+        assert_eq!(config.profile(), DEFAULT_PROFILE);
+
+        config.set_value("seed", Some("42")).unwrap();
+        assert_eq!(config.seed, Some(42));
+
+        config
+            .set_value("allowed_origins", Some(r#"["http://localhost:3000"]"#))
+            .unwrap();
+        assert_eq!(config.allowed_origins, ["http://localhost:3000"]);
+
+        assert!(matches!(
+            config.set_value("max_guesses", Some("lots")),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            config.set_value("colour", Some("red")),
+            Err(ConfigError::UnknownKey(_))
+        ));
+        assert_eq!(config.max_guesses, 8);
+
+        config
+            .set_value("past_answer_weight", Some("0.25"))
+            .unwrap();
+        assert!(matches!(
+            config.set_value("past_answer_weight", Some("1.5")),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert_eq!(config.past_answer_weight, 0.25);
+    }
+
+    #[test]
+    fn test_removed_keys_are_ignored() {
+        let config: Config =
+            toml::from_str("word_list_path = \"words.txt\"\nmax_guesses = 7").unwrap();
+        assert_eq!(config.max_guesses, 7);
+        assert!(matches!(
+            Config::default().set_value("word_list_path", Some("x")),
+            Err(ConfigError::UnknownKey(_))
+        ));
+    }
+}
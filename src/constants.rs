@@ -1,2 +1,6 @@
 pub const EXPECTED_FORMAT: &str = "gyngy";
 pub const WORD_LENGTH: usize = 5;
+/// Name of the guess selection strategy, stored with sessions so resumed and replayed games use the same one.
+pub const STRATEGY: &str = "positional-frequency";
+/// The built-in word list, embedded in the binary.
+pub const WORD_LIST: &str = include_str!("words.txt");
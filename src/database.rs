@@ -1,20 +1,245 @@
-use crate::{config::Config, session::SessionResults};
+use crate::{
+    archive::ArchiveRunSummary,
+    benchmark::{BenchmarkRun, WordResult},
+    config::{Config, DEFAULT_PROFILE},
+    past_answers::PastAnswer,
+    session::{ActiveSession, GuessRecord, SessionResults},
+};
 
 use super::word_analyzer::Word;
-use rusqlite::{Connection, Result, params};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Result, params, params_from_iter};
+use std::collections::HashSet;
+use std::path::Path;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// A single schema migration step. Steps are applied in order and the step at index `n` upgrades the database to `user_version` `n + 1`.
+type Migration = fn(&Connection) -> Result<(), rusqlite::Error>;
+
+/// Ordered list of schema migrations for the results database. Only ever append to this list, never reorder or edit a released step.
+const MIGRATIONS: &[Migration] = &[
+    create_session_results,
+    import_legacy_game_results,
+    create_session_guesses,
+    create_active_sessions,
+    create_word_sources,
+    create_past_answers,
+    create_archive_runs,
+    add_profiles,
+    add_session_status,
+    add_session_seed,
+    create_benchmark_runs,
+    create_benchmark_baselines,
+];
+
+/// The schema version this build of crackle writes and understands.
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Rusqlite(#[from] rusqlite::Error),
+
+    #[error(
+        "Database schema version {found} is newer than the supported version {supported}, please update crackle"
+    )]
+    UnsupportedVersion { found: i32, supported: i32 },
+}
+
+/// Optional filters applied when reading stored sessions back out of `session_results`.
+#[derive(Debug, Default, Clone)]
+pub struct SessionFilter {
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    pub session_type: Option<String>,
+    pub profile: Option<String>,
+}
+
+impl SessionFilter {
+    /// Filters on sessions started between the two dates, both inclusive.
+    pub fn between(since: Option<chrono::NaiveDate>, until: Option<chrono::NaiveDate>) -> Self {
+        SessionFilter {
+            since: since
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc()),
+            until: until
+                .and_then(|date| date.and_hms_opt(23, 59, 59))
+                .map(|date| date.and_utc()),
+            ..SessionFilter::default()
+        }
+    }
+}
+
+/// A word list registered under a name, whose analysed words are stored in the results database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordSource {
+    pub name: String,
+    pub path: String,
+    pub source_hash: String,
+    pub word_count: u32,
+    pub added_date: chrono::DateTime<chrono::Utc>,
+}
+
 /// This is a wrapper for the database to easily interact with it.
 pub struct DB {
     conn: Connection,
 }
 
 impl DB {
-    /// Creates a new instance of the database.
-    /// This function is only called if the database does not already exist.
+    /// Opens the results database at the path in the config, creating the file if it does not exist.
+    /// Call `migrate` afterwards to bring the schema up to date.
     pub fn new(config: &Config) -> Result<Self, rusqlite::Error> {
         Ok(Self {
             conn: Connection::open(&config.app_db)?,
         })
     }
+
+    /// Opens the results database read-only, for commands that must not change anything.
+    /// Returns `None` when the file does not exist yet, instead of creating it.
+    pub fn open_read_only(config: &Config) -> Result<Option<Self>, rusqlite::Error> {
+        if !config.app_db.exists() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            conn: Connection::open_with_flags(&config.app_db, OpenFlags::SQLITE_OPEN_READ_ONLY)?,
+        }))
+    }
+
+    /// Returns the schema version stored in SQLite's `user_version` pragma.
+    pub fn schema_version(&self) -> Result<i32, rusqlite::Error> {
+        self.conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+    }
+
+    /// Upgrades the database schema in place by applying every migration newer than the stored `user_version`.
+    /// Each step runs in its own transaction together with the version bump, so a failed step leaves the database at the previous version.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MigrationError::UnsupportedVersion` if the database was written by a newer version of crackle.
+    pub fn migrate(&self) -> Result<(), MigrationError> {
+        let current = self.schema_version()?;
+        if current > SCHEMA_VERSION {
+            return Err(MigrationError::UnsupportedVersion {
+                found: current,
+                supported: SCHEMA_VERSION,
+            });
+        }
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            let tx = self.conn.unchecked_transaction()?;
+            migration(&tx)?;
+            tx.pragma_update(None, "user_version", (i + 1) as i32)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads an RFC 3339 text column as a UTC date.
+fn parse_date_column(
+    row: &rusqlite::Row,
+    idx: usize,
+) -> Result<chrono::DateTime<chrono::Utc>, rusqlite::Error> {
+    let text: String = row.get(idx)?;
+    chrono::DateTime::parse_from_rfc3339(&text)
+        .map(|d| d.with_timezone(&chrono::Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+/// Maps a `session_results` row selected in column order back into a `SessionResults`.
+fn session_results_from_row(row: &rusqlite::Row) -> Result<SessionResults, rusqlite::Error> {
+    let session_id: String = row.get(0)?;
+    let status: String = row.get(8)?;
+    let outcome = status.parse().map_err(|e: String| {
+        rusqlite::Error::FromSqlConversionFailure(8, rusqlite::types::Type::Text, e.into())
+    })?;
+    Ok(SessionResults {
+        session_id: Uuid::parse_str(&session_id).unwrap_or_else(|_| Uuid::nil()),
+        start_date: parse_date_column(row, 1)?,
+        end_date: parse_date_column(row, 2)?,
+        session_type: row.get(3)?,
+        word: row.get(4)?,
+        number_of_guesses: row.get(5)?,
+        win: row.get(6)?,
+        profile: row.get(7)?,
+        outcome,
+        seed: row.get::<_, Option<i64>>(9)?.map(|seed| seed as u64),
+    })
+}
+
+/// Migration 1: creates the `session_results` table and its indexes.
+/// Databases created before migrations existed already have this table, so every statement is idempotent.
+fn create_session_results(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_results (
+            session_id        TEXT PRIMARY KEY NOT NULL,
+            start_date        TEXT NOT NULL,
+            end_date          TEXT NOT NULL,
+            session_type      TEXT NOT NULL,
+            word              TEXT NOT NULL,
+            number_of_guesses INTEGER NOT NULL,
+            win               BOOLEAN NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS session_guess_idx ON session_results (number_of_guesses);
+        CREATE INDEX IF NOT EXISTS session_type_idx ON session_results (session_type);",
+    )
+}
+
+/// Migration 2: moves rows from the legacy `game_results` table into `session_results` and drops the old table.
+/// Legacy rows only stored a date, so it is used as both the start and end of the session.
+fn import_legacy_game_results(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let legacy_table: Option<String> = conn
+        .query_row(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'game_results'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if legacy_table.is_none() {
+        return Ok(());
+    }
+
+    let rows = {
+        let mut stmt =
+            conn.prepare("SELECT word, date, win, number_of_guesses FROM game_results")?;
+        let row_iter = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<bool>>(2)?,
+                row.get::<_, Option<u8>>(3)?,
+            ))
+        })?;
+        row_iter.collect::<Result<Vec<_>, _>>()?
+    };
+
+    let mut insert = conn.prepare(
+        "INSERT INTO session_results (
+            session_id, start_date, end_date, session_type, word, number_of_guesses, win
+        ) VALUES (?1, ?2, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (word, date, win, number_of_guesses) in rows {
+        let date = date
+            .as_deref()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc())
+            .unwrap_or_else(chrono::Utc::now);
+        insert.execute(params![
+            Uuid::new_v4().to_string(),
+            date.to_rfc3339(),
+            "Interactive",
+            word.unwrap_or_default(),
+            number_of_guesses.unwrap_or(0),
+            win.unwrap_or(false),
+        ])?;
+    }
+
+    conn.execute("DROP TABLE game_results", [])?;
+    Ok(())
 }
 
 impl DB {
@@ -95,7 +320,7 @@ impl DB {
     /// Stores the complete results of a game session in the database.
     ///
     /// This function takes all the data from a `SessionResults` struct and inserts it
-    /// as a new row in the `session_results` table. It handles the conversion of
+    /// as a row in the `session_results` table, replacing the row of an abandoned game that was resumed. It handles the conversion of
     /// complex types like `Uuid` and `DateTime<Utc>` into a text format suitable for SQLite.
     ///
     /// # Arguments
@@ -113,15 +338,18 @@ impl DB {
     ) -> Result<(), rusqlite::Error> {
         // The SQL statement now includes all the columns from the session_results table.
         let sql = "
-               INSERT INTO session_results (
+               INSERT OR REPLACE INTO session_results (
                    session_id,
                    start_date,
                    end_date,
                    session_type,
                    word,
                    number_of_guesses,
-                   win
-               ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                   win,
+                   profile,
+                   status,
+                   seed
+               ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
            ";
 
         let mut stmt = self.conn.prepare(sql)?;
@@ -134,11 +362,519 @@ impl DB {
             session_results.word,
             session_results.number_of_guesses,
             session_results.win,
+            session_results.profile,
+            session_results.outcome.as_str(),
+            // SQLite integers are signed, so the seed is stored with the same bits as an i64.
+            session_results.seed.map(|seed| seed as i64),
         ])?;
 
         Ok(())
     }
 
+    /// Reads stored sessions matching the filter, ordered from oldest to newest.
+    ///
+    /// Dates are stored as RFC 3339 text in UTC, so the range filters compare them as strings.
+    pub fn get_session_results(
+        &self,
+        filter: &SessionFilter,
+    ) -> Result<Vec<SessionResults>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, start_date, end_date, session_type, word, number_of_guesses, win, profile, status, seed
+             FROM session_results
+             WHERE (?1 IS NULL OR start_date >= ?1)
+               AND (?2 IS NULL OR start_date <= ?2)
+               AND (?3 IS NULL OR session_type = ?3)
+               AND (?4 IS NULL OR profile = ?4)
+             ORDER BY start_date ASC",
+        )?;
+
+        let rows = stmt.query_map(
+            params![
+                filter.since.map(|d| d.to_rfc3339()),
+                filter.until.map(|d| d.to_rfc3339()),
+                filter.session_type,
+                filter.profile,
+            ],
+            session_results_from_row,
+        )?;
+        rows.collect()
+    }
+
+    /// Returns true if a session with this id is already stored.
+    pub fn session_exists(&self, session_id: &str) -> Result<bool, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM session_results WHERE session_id = ?1)",
+            params![session_id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Runs `f` in one transaction and commits only if it succeeds, so a failure part way leaves the database unchanged.
+    pub fn transaction<T, E: From<rusqlite::Error>>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let tx = self.conn.unchecked_transaction()?;
+        let value = f(self)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    /// Stores the guess and feedback history of a session, replacing any history already stored for it.
+    /// Inside `transaction` it joins the open transaction instead of starting its own.
+    pub fn store_guesses(
+        &self,
+        session_id: &Uuid,
+        guesses: &[GuessRecord],
+    ) -> Result<(), rusqlite::Error> {
+        let own_tx = if self.conn.is_autocommit() {
+            Some(self.conn.unchecked_transaction()?)
+        } else {
+            None
+        };
+        let tx: &Connection = own_tx.as_deref().unwrap_or(&self.conn);
+        tx.execute(
+            "DELETE FROM session_guesses WHERE session_id = ?1",
+            params![session_id.to_string()],
+        )?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO session_guesses (session_id, turn, guess, feedback) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (turn, record) in guesses.iter().enumerate() {
+                stmt.execute(params![
+                    session_id.to_string(),
+                    turn as u32 + 1,
+                    record.guess,
+                    record.feedback,
+                ])?;
+            }
+        }
+        match own_tx {
+            Some(tx) => tx.commit(),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the guess and feedback history of a session in turn order. Sessions stored before guess history existed return an empty list.
+    pub fn get_guesses(&self, session_id: &Uuid) -> Result<Vec<GuessRecord>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT guess, feedback FROM session_guesses WHERE session_id = ?1 ORDER BY turn ASC",
+        )?;
+        let rows = stmt.query_map(params![session_id.to_string()], |row| {
+            Ok(GuessRecord {
+                guess: row.get(0)?,
+                feedback: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Saves the state of an unfinished session so it can be resumed, replacing any earlier save of the same session.
+    pub fn save_active_session(&self, active: &ActiveSession) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO active_sessions (
+                session_id, start_date, updated_date, session_type, current_guess, strategy, seed, profile
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                active.session_id.to_string(),
+                active.start_date.to_rfc3339(),
+                active.updated_date.to_rfc3339(),
+                active.session_type,
+                active.current_guess,
+                active.strategy,
+                active.seed.map(|seed| seed as i64),
+                active.profile,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Lists the unfinished sessions of a profile last updated at or after `since`, most recent first.
+    pub fn get_active_sessions(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        profile: &str,
+    ) -> Result<Vec<ActiveSession>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, start_date, updated_date, session_type, current_guess, strategy, seed, profile
+             FROM active_sessions
+             WHERE updated_date >= ?1 AND profile = ?2
+             ORDER BY updated_date DESC",
+        )?;
+        let rows = stmt.query_map(params![since.to_rfc3339(), profile], |row| {
+            let session_id: String = row.get(0)?;
+            Ok(ActiveSession {
+                session_id: Uuid::parse_str(&session_id).unwrap_or_else(|_| Uuid::nil()),
+                start_date: parse_date_column(row, 1)?,
+                updated_date: parse_date_column(row, 2)?,
+                session_type: row.get(3)?,
+                current_guess: row.get(4)?,
+                strategy: row.get(5)?,
+                seed: row.get::<_, Option<i64>>(6)?.map(|seed| seed as u64),
+                profile: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Removes the saved state of a session once it has finished.
+    pub fn delete_active_session(&self, session_id: &Uuid) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "DELETE FROM active_sessions WHERE session_id = ?1",
+            params![session_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Registers a profile. Adding a profile that already exists does nothing.
+    pub fn add_profile(&self, name: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO profiles (name, created_date) VALUES (?1, ?2)",
+            params![name, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Lists the registered profiles by name.
+    pub fn get_profiles(&self) -> Result<Vec<String>, rusqlite::Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM profiles ORDER BY name ASC")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Stores an analysed word source under its name, replacing any source already registered with that name.
+    pub fn store_word_source(
+        &self,
+        source: &WordSource,
+        words: &[Word],
+    ) -> Result<(), rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM source_words WHERE source_name = ?1",
+            params![source.name],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO word_sources (name, path, source_hash, word_count, added_date)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                source.name,
+                source.path,
+                source.source_hash,
+                source.word_count,
+                source.added_date.to_rfc3339(),
+            ],
+        )?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO source_words (source_name, word, total_probability) VALUES (?1, ?2, ?3)",
+            )?;
+            for word in words {
+                stmt.execute(params![source.name, word.as_str(), word.total_probability])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Lists the registered word sources in name order.
+    pub fn get_word_sources(&self) -> Result<Vec<WordSource>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, path, source_hash, word_count, added_date FROM word_sources ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(WordSource {
+                name: row.get(0)?,
+                path: row.get(1)?,
+                source_hash: row.get(2)?,
+                word_count: row.get(3)?,
+                added_date: parse_date_column(row, 4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Copies the stored words of a registered source into a new in-memory word database.
+    /// Returns `None` if no source with that name is registered.
+    pub fn load_word_source(&self, name: &str) -> Result<Option<Self>, rusqlite::Error> {
+        let registered: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM word_sources WHERE name = ?1)",
+            params![name],
+            |row| row.get(0),
+        )?;
+        if !registered {
+            return Ok(None);
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT word, total_probability FROM source_words WHERE source_name = ?1 ORDER BY rowid",
+        )?;
+        let rows = stmt.query_map(params![name], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?;
+        let mut words = Vec::new();
+        for row in rows {
+            let (word, probability) = row?;
+            if let Ok(word) = Word::new(0, probability, &word) {
+                words.push(word);
+            }
+        }
+
+        let db = Self::new_in_memory()?;
+        db.batch_insert(&words)?;
+        Ok(Some(db))
+    }
+
+    /// Removes a registered word source and its words.
+    pub fn delete_word_source(&self, name: &str) -> Result<(), rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM source_words WHERE source_name = ?1",
+            params![name],
+        )?;
+        tx.execute("DELETE FROM word_sources WHERE name = ?1", params![name])?;
+        tx.commit()
+    }
+
+    /// Stores past official answers, keeping the known date when a word is stored again without one.
+    /// Returns the number of answers that were not stored before.
+    pub fn store_past_answers(
+        &self,
+        answers: &[PastAnswer],
+        source: &str,
+    ) -> Result<usize, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut added = 0;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO past_answers (word, answer_date, source) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(word) DO UPDATE SET answer_date = COALESCE(excluded.answer_date, answer_date)",
+            )?;
+            for answer in answers {
+                let existed: bool = tx.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM past_answers WHERE word = ?1)",
+                    params![answer.word],
+                    |row| row.get(0),
+                )?;
+                stmt.execute(params![
+                    answer.word,
+                    answer.date.map(|d| d.format("%Y-%m-%d").to_string()),
+                    source,
+                ])?;
+                if !existed {
+                    added += 1;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// Returns every stored past answer.
+    pub fn get_past_answers(&self) -> Result<HashSet<String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare("SELECT word FROM past_answers")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Collects the answers of interactive sessions whose word is fully known, with the day the session started.
+    pub fn get_known_session_answers(&self) -> Result<Vec<PastAnswer>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT word, MIN(substr(start_date, 1, 10)) FROM session_results
+             WHERE session_type = 'Interactive' AND status != 'abandoned'
+               AND length(word) = 5 AND instr(word, '_') = 0
+             GROUP BY word",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let date: Option<String> = row.get(1)?;
+            Ok(PastAnswer {
+                word: row.get(0)?,
+                date: date.and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Removes every stored past answer.
+    pub fn clear_past_answers(&self) -> Result<(), rusqlite::Error> {
+        self.conn.execute("DELETE FROM past_answers", [])?;
+        Ok(())
+    }
+
+    /// Links a stored session to the archived puzzle it replayed and the crackle version that played it.
+    pub fn store_archive_run(
+        &self,
+        session_id: &Uuid,
+        puzzle_number: u32,
+        answer: &str,
+        crackle_version: &str,
+    ) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO archive_runs (session_id, puzzle_number, answer, crackle_version)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                session_id.to_string(),
+                puzzle_number,
+                answer,
+                crackle_version
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Summarises archive runs of the given puzzle numbers, one row per crackle version.
+    pub fn get_archive_summaries(
+        &self,
+        puzzles: &[u32],
+    ) -> Result<Vec<ArchiveRunSummary>, rusqlite::Error> {
+        if puzzles.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; puzzles.len()].join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT a.crackle_version,
+                    COUNT(*),
+                    SUM(s.win),
+                    AVG(CASE WHEN s.win THEN s.number_of_guesses END)
+             FROM archive_runs a
+             JOIN session_results s ON s.session_id = a.session_id
+             WHERE a.puzzle_number IN ({placeholders})
+             GROUP BY a.crackle_version
+             ORDER BY a.crackle_version"
+        ))?;
+        let rows = stmt.query_map(params_from_iter(puzzles), |row| {
+            Ok(ArchiveRunSummary {
+                crackle_version: row.get(0)?,
+                runs: row.get(1)?,
+                wins: row.get(2)?,
+                average_guesses: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Stores a benchmark run and the result for each of its words, returning the id of the run.
+    /// The word count, wins and average are stored too, so runs can be listed without reading every word.
+    pub fn store_benchmark_run(&self, run: &BenchmarkRun) -> Result<i64, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO benchmark_runs (
+                created_date, crackle_version, strategy, word_source, word_source_hash, subset, seed, opener,
+                max_guesses, words, wins, average_guesses
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                run.created.to_rfc3339(),
+                run.crackle_version,
+                run.strategy,
+                run.word_source,
+                run.word_source_hash,
+                run.subset,
+                run.seed as i64,
+                run.opener,
+                run.max_guesses,
+                run.results.len(),
+                run.wins(),
+                run.average_guesses(),
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO benchmark_words (run_id, word, status, guesses) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for result in &run.results {
+                stmt.execute(params![
+                    run_id,
+                    result.word,
+                    result.outcome.as_str(),
+                    result.guesses
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Reads a stored benchmark run with its word results in alphabetical order. `None` if no run has this id.
+    pub fn get_benchmark_run(&self, run_id: i64) -> Result<Option<BenchmarkRun>, rusqlite::Error> {
+        let run = self
+            .conn
+            .query_row(
+                "SELECT id, created_date, crackle_version, strategy, word_source, word_source_hash, subset, seed,
+                        opener, max_guesses
+                 FROM benchmark_runs WHERE id = ?1",
+                params![run_id],
+                |row| {
+                    Ok(BenchmarkRun {
+                        id: row.get(0)?,
+                        created: parse_date_column(row, 1)?,
+                        crackle_version: row.get(2)?,
+                        strategy: row.get(3)?,
+                        word_source: row.get(4)?,
+                        word_source_hash: row.get(5)?,
+                        subset: row.get(6)?,
+                        seed: row.get::<_, i64>(7)? as u64,
+                        opener: row.get(8)?,
+                        max_guesses: row.get(9)?,
+                        results: Vec::new(),
+                    })
+                },
+            )
+            .optional()?;
+        let Some(mut run) = run else {
+            return Ok(None);
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT word, status, guesses FROM benchmark_words WHERE run_id = ?1 ORDER BY word",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| {
+            let status: String = row.get(1)?;
+            Ok(WordResult {
+                word: row.get(0)?,
+                outcome: status.parse().map_err(|e: String| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        1,
+                        rusqlite::types::Type::Text,
+                        e.into(),
+                    )
+                })?,
+                guesses: row.get(2)?,
+            })
+        })?;
+        run.results = rows.collect::<Result<_, _>>()?;
+        Ok(Some(run))
+    }
+
+    /// Names a stored benchmark run as a baseline, replacing any baseline with that name.
+    pub fn save_benchmark_baseline(&self, name: &str, run_id: i64) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO benchmark_baselines (name, run_id, created_date) VALUES (?1, ?2, ?3)",
+            params![name, run_id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Reads the benchmark run saved as the baseline `name`. `None` if there is no such baseline.
+    pub fn get_benchmark_baseline(
+        &self,
+        name: &str,
+    ) -> Result<Option<BenchmarkRun>, rusqlite::Error> {
+        let run_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT run_id FROM benchmark_baselines WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match run_id {
+            Some(run_id) => self.get_benchmark_run(run_id),
+            None => Ok(None),
+        }
+    }
+
     /// Retrieves the ID of a word from the database.
     ///
     /// # Arguments
@@ -155,6 +891,22 @@ impl DB {
         Ok(word_id)
     }
 
+    /// Every word in the words table in alphabetical order.
+    pub fn get_words(&self) -> Result<Vec<String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare("SELECT word FROM words ORDER BY word")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Returns true if the word is in the words table.
+    pub fn contains_word(&self, w: &str) -> Result<bool, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM words WHERE word = ?1)",
+            params![w],
+            |row| row.get(0),
+        )
+    }
+
     /// Filters words in the database based on a LIKE pattern built by the gameloop struct in game_loop.rs.
     ///
     /// # Arguments
@@ -175,16 +927,19 @@ impl DB {
         word_iter.collect()
     }
 
-    pub fn get_random_word(&self) -> Result<String, rusqlite::Error> {
-        let mut stmt = self
+    /// Picks a word with `rng`. Words are taken in alphabetical order, so the same seed and word list always give the same word.
+    pub fn get_random_word(&self, rng: &mut impl rand::Rng) -> Result<String, rusqlite::Error> {
+        let count: i64 = self
             .conn
-            .prepare("SELECT word FROM words ORDER BY RANDOM() LIMIT 1")?;
-
-        let word_iter = stmt.query_map(params![], |row| row.get(0))?;
-
-        word_iter
-            .collect::<Result<Vec<String>, _>>()
-            .map(|vec| vec[0].clone())
+            .query_row("SELECT COUNT(*) FROM words", [], |row| row.get(0))?;
+        if count == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        self.conn.query_row(
+            "SELECT word FROM words ORDER BY word LIMIT 1 OFFSET ?1",
+            params![rng.gen_range(0..count)],
+            |row| row.get(0),
+        )
     }
 
     pub fn delete_words(&self) -> Result<(), rusqlite::Error> {
@@ -243,6 +998,80 @@ impl DB {
 
         Ok(())
     }
+    /// Creates an in-memory results database with the full schema applied, useful for tests and dry runs.
+    pub fn new_results_in_memory() -> Result<Self, MigrationError> {
+        let db = Self {
+            conn: Connection::open_in_memory()?,
+        };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    /// Loads a word cache file written by `write_word_cache` into a new in-memory database.
+    /// Returns `None` if the file is missing, is not a word cache, or was written with a different key.
+    pub fn load_word_cache(path: &Path, key: &str) -> Result<Option<Self>, rusqlite::Error> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let cached_key: Option<String> = {
+            let cache = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            cache
+                .query_row("SELECT key FROM cache_meta", [], |row| row.get(0))
+                .optional()
+                .unwrap_or(None)
+        };
+        if cached_key.as_deref() != Some(key) {
+            return Ok(None);
+        }
+
+        let db = Self::new_in_memory()?;
+        db.conn.execute(
+            "ATTACH DATABASE ?1 AS cache",
+            params![path.to_string_lossy()],
+        )?;
+        db.conn.execute_batch(
+            "INSERT INTO words (word, total_probability)
+                SELECT word, total_probability FROM cache.words ORDER BY id;
+             DETACH DATABASE cache;",
+        )?;
+        Ok(Some(db))
+    }
+
+    /// Writes the words table to a cache file tagged with `key`, replacing any existing cache.
+    pub fn write_word_cache(&self, path: &Path, key: &str) -> Result<(), rusqlite::Error> {
+        let cache = Connection::open(path)?;
+        cache.execute_batch(
+            "DROP TABLE IF EXISTS words;
+            DROP TABLE IF EXISTS cache_meta;
+            CREATE TABLE words (
+                id INTEGER PRIMARY KEY autoincrement,
+                total_probability REAL,
+                word VARCHAR(5)
+            );
+            CREATE TABLE cache_meta (key TEXT NOT NULL);",
+        )?;
+        drop(cache);
+
+        self.conn.execute(
+            "ATTACH DATABASE ?1 AS cache",
+            params![path.to_string_lossy()],
+        )?;
+        let copied = self.conn.execute_batch(
+            "INSERT INTO cache.words (word, total_probability)
+                SELECT word, total_probability FROM words ORDER BY id;",
+        );
+        let tagged = copied.and_then(|_| {
+            self.conn
+                .execute(
+                    "INSERT INTO cache.cache_meta (key) VALUES (?1)",
+                    params![key],
+                )
+                .map(|_| ())
+        });
+        self.conn.execute_batch("DETACH DATABASE cache;")?;
+        tagged
+    }
+
     pub fn new_in_memory() -> Result<Self, rusqlite::Error> {
         let conn = Connection::open_in_memory()?;
         let db = Self { conn };
@@ -251,3 +1080,634 @@ impl DB {
         Ok(db)
     }
 }
+
+/// Migration 3: adds per-turn guess and feedback history for each session.
+fn create_session_guesses(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS session_guesses (
+            session_id TEXT NOT NULL,
+            turn       INTEGER NOT NULL,
+            guess      TEXT NOT NULL,
+            feedback   TEXT NOT NULL,
+            PRIMARY KEY (session_id, turn)
+        );",
+    )
+}
+
+/// Migration 4: adds saved state for unfinished sessions so they can be resumed. The guess history lives in `session_guesses`.
+fn create_active_sessions(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS active_sessions (
+            session_id    TEXT PRIMARY KEY NOT NULL,
+            start_date    TEXT NOT NULL,
+            updated_date  TEXT NOT NULL,
+            session_type  TEXT NOT NULL,
+            current_guess TEXT NOT NULL,
+            strategy      TEXT NOT NULL,
+            seed          INTEGER
+        );",
+    )
+}
+
+/// Migration 5: adds named word sources and their analysed words.
+fn create_word_sources(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS word_sources (
+            name        TEXT PRIMARY KEY NOT NULL,
+            path        TEXT NOT NULL,
+            source_hash TEXT NOT NULL,
+            word_count  INTEGER NOT NULL,
+            added_date  TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS source_words (
+            source_name       TEXT NOT NULL,
+            word              TEXT NOT NULL,
+            total_probability REAL NOT NULL,
+            PRIMARY KEY (source_name, word)
+        );",
+    )
+}
+
+/// Migration 6: adds the list of previously used official answers.
+fn create_past_answers(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS past_answers (
+            word        TEXT PRIMARY KEY NOT NULL,
+            answer_date TEXT,
+            source      TEXT NOT NULL
+        );",
+    )
+}
+
+/// Migration 7: links sessions that replayed an archived puzzle to the puzzle number and crackle version.
+fn create_archive_runs(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS archive_runs (
+            session_id      TEXT PRIMARY KEY NOT NULL,
+            puzzle_number   INTEGER NOT NULL,
+            answer          TEXT NOT NULL,
+            crackle_version TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS archive_puzzle_idx ON archive_runs (puzzle_number);",
+    )
+}
+
+/// Migration 8: adds player profiles. Sessions stored before profiles existed belong to the default profile.
+fn add_profiles(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS profiles (
+            name         TEXT PRIMARY KEY NOT NULL,
+            created_date TEXT NOT NULL
+        );
+        ALTER TABLE session_results ADD COLUMN profile TEXT NOT NULL DEFAULT '{DEFAULT_PROFILE}';
+        ALTER TABLE active_sessions ADD COLUMN profile TEXT NOT NULL DEFAULT '{DEFAULT_PROFILE}';
+        CREATE INDEX IF NOT EXISTS profile_idx ON session_results (profile);
+        INSERT OR IGNORE INTO profiles (name, created_date) VALUES ('{DEFAULT_PROFILE}', '{}');",
+        chrono::Utc::now().to_rfc3339()
+    ))
+}
+
+/// Migration 9: records how each session ended. Sessions stored before this were either won or lost.
+fn add_session_status(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "ALTER TABLE session_results ADD COLUMN status TEXT NOT NULL DEFAULT 'lost';
+        UPDATE session_results SET status = CASE WHEN win THEN 'won' ELSE 'lost' END;
+        CREATE INDEX IF NOT EXISTS status_idx ON session_results (status);",
+    )
+}
+
+/// Migration 10: records the seed of each session's random choices. Sessions stored before this have none.
+fn add_session_seed(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch("ALTER TABLE session_results ADD COLUMN seed INTEGER;")
+}
+
+/// Migration 11: stores benchmark runs with the result for every target word.
+fn create_benchmark_runs(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS benchmark_runs (
+            id               INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_date     TEXT NOT NULL,
+            crackle_version  TEXT NOT NULL,
+            strategy         TEXT NOT NULL,
+            word_source      TEXT NOT NULL,
+            word_source_hash TEXT NOT NULL,
+            subset           TEXT,
+            seed             INTEGER NOT NULL,
+            opener           TEXT NOT NULL,
+            max_guesses      INTEGER NOT NULL,
+            words            INTEGER NOT NULL,
+            wins             INTEGER NOT NULL,
+            average_guesses  REAL
+        );
+        CREATE TABLE IF NOT EXISTS benchmark_words (
+            run_id  INTEGER NOT NULL REFERENCES benchmark_runs (id) ON DELETE CASCADE,
+            word    TEXT NOT NULL,
+            status  TEXT NOT NULL,
+            guesses INTEGER NOT NULL,
+            PRIMARY KEY (run_id, word)
+        );",
+    )
+}
+
+/// Migration 12: names benchmark runs that later runs are compared against.
+fn create_benchmark_baselines(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS benchmark_baselines (
+            name         TEXT PRIMARY KEY NOT NULL,
+            run_id       INTEGER NOT NULL REFERENCES benchmark_runs (id),
+            created_date TEXT NOT NULL
+        );",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionOutcome;
+
+    fn empty_db() -> DB {
+        DB {
+            conn: Connection::open_in_memory().unwrap(),
+        }
+    }
+
+    fn count_sessions(db: &DB) -> i64 {
+        db.conn
+            .query_row("SELECT COUNT(*) FROM session_results", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_migrate_fresh_database() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+        assert_eq!(count_sessions(&db), 0);
+    }
+
+    #[test]
+    fn test_migrate_is_idempotent() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        db.migrate().unwrap();
+        assert_eq!(db.schema_version().unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_unversioned_database_keeps_sessions() {
+        let db = empty_db();
+        db.create_session_table().unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO session_results VALUES ('id', 'start', 'end', 'Test', 'apple', 3, 1)",
+                [],
+            )
+            .unwrap();
+
+        db.migrate().unwrap();
+        assert_eq!(count_sessions(&db), 1);
+        let profile: String = db
+            .conn
+            .query_row("SELECT profile FROM session_results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(profile, DEFAULT_PROFILE);
+        let status: String = db
+            .conn
+            .query_row("SELECT status FROM session_results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(status, "won");
+    }
+
+    #[test]
+    fn test_migrate_moves_legacy_game_results() {
+        let db = empty_db();
+        db.conn
+            .execute_batch(
+                "CREATE TABLE game_results (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    word VARCHAR(5),
+                    date DATE DEFAULT CURRENT_DATE,
+                    win BOOLEAN NOT NULL,
+                    number_of_guesses INTEGER
+                );
+                INSERT INTO game_results (word, date, win, number_of_guesses) VALUES ('crane', '2025-07-01', 1, 4);
+                INSERT INTO game_results (word, date, win, number_of_guesses) VALUES ('slate', '2025-07-02', 0, 6);",
+            )
+            .unwrap();
+
+        db.migrate().unwrap();
+        assert_eq!(count_sessions(&db), 2);
+
+        let (start_date, win): (String, bool) = db
+            .conn
+            .query_row(
+                "SELECT start_date, win FROM session_results WHERE word = 'crane'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(start_date.starts_with("2025-07-01T00:00:00"));
+        assert!(win);
+
+        let legacy: Option<String> = db
+            .conn
+            .query_row(
+                "SELECT name FROM sqlite_master WHERE name = 'game_results'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .unwrap();
+        assert!(legacy.is_none());
+    }
+
+    fn sample_results(session_type: &str, day: u32, win: bool) -> SessionResults {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 7, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+        SessionResults {
+            session_id: Uuid::new_v4(),
+            start_date: date,
+            end_date: date,
+            session_type: session_type.to_string(),
+            word: "crane".to_string(),
+            number_of_guesses: 4,
+            win,
+            profile: DEFAULT_PROFILE.to_string(),
+            outcome: if win {
+                SessionOutcome::Won
+            } else {
+                SessionOutcome::Lost
+            },
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn test_get_session_results_filters() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        db.store_session_results(&sample_results("Interactive", 1, true))
+            .unwrap();
+        db.store_session_results(&sample_results("Test", 2, false))
+            .unwrap();
+        db.store_session_results(&sample_results("Interactive", 3, false))
+            .unwrap();
+
+        let all = db.get_session_results(&SessionFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all[0].start_date < all[2].start_date);
+
+        let interactive = db
+            .get_session_results(&SessionFilter {
+                session_type: Some("Interactive".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(interactive.len(), 2);
+
+        let since_second = db
+            .get_session_results(&SessionFilter {
+                since: Some(all[1].start_date),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(since_second.len(), 2);
+    }
+
+    #[test]
+    fn test_finished_game_replaces_abandoned_record() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let mut results = sample_results("Interactive", 1, false);
+        results.word = "cr___".to_string();
+        results.outcome = SessionOutcome::Abandoned;
+        db.store_session_results(&results).unwrap();
+        assert!(db.get_known_session_answers().unwrap().is_empty());
+
+        results.word = "crane".to_string();
+        results.win = true;
+        results.outcome = SessionOutcome::Won;
+        db.store_session_results(&results).unwrap();
+        let stored = db.get_session_results(&SessionFilter::default()).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].outcome, SessionOutcome::Won);
+        assert_eq!(db.get_known_session_answers().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_session_seed_round_trip() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let mut seeded = sample_results("Test", 1, true);
+        seeded.seed = Some(u64::MAX);
+        db.store_session_results(&seeded).unwrap();
+        db.store_session_results(&sample_results("Test", 2, true))
+            .unwrap();
+
+        let stored = db.get_session_results(&SessionFilter::default()).unwrap();
+        assert_eq!(stored[0].seed, Some(u64::MAX));
+        assert_eq!(stored[1].seed, None);
+    }
+
+    #[test]
+    fn test_random_word_follows_seed() {
+        use rand::SeedableRng;
+        let db = crate::setup::setup_word_db("crane\nslate\ngrate\nirate\nplate").unwrap();
+        let pick = |seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            (0..5)
+                .map(|_| db.get_random_word(&mut rng).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pick(7), pick(7));
+        assert!(
+            DB::new_in_memory()
+                .unwrap()
+                .get_random_word(&mut rand::thread_rng())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_profiles_split_sessions() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        assert_eq!(db.get_profiles().unwrap(), vec![DEFAULT_PROFILE]);
+        db.add_profile("sam").unwrap();
+        db.add_profile("sam").unwrap();
+        assert_eq!(db.get_profiles().unwrap(), vec![DEFAULT_PROFILE, "sam"]);
+
+        let mut theirs = sample_results("Interactive", 2, true);
+        theirs.profile = "sam".to_string();
+        db.store_session_results(&sample_results("Interactive", 1, false))
+            .unwrap();
+        db.store_session_results(&theirs).unwrap();
+
+        let filter = |profile: &str| SessionFilter {
+            profile: Some(profile.to_string()),
+            ..Default::default()
+        };
+        let sam = db.get_session_results(&filter("sam")).unwrap();
+        assert_eq!(sam.len(), 1);
+        assert!(sam[0].win);
+        assert_eq!(
+            db.get_session_results(&filter(DEFAULT_PROFILE))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.get_session_results(&SessionFilter::default())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_store_and_get_guesses() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let session_id = Uuid::new_v4();
+        let guesses = vec![
+            GuessRecord {
+                guess: "crane".to_string(),
+                feedback: "nnygy".to_string(),
+            },
+            GuessRecord {
+                guess: "slate".to_string(),
+                feedback: "ggggg".to_string(),
+            },
+        ];
+
+        db.store_guesses(&session_id, &guesses).unwrap();
+        assert_eq!(db.get_guesses(&session_id).unwrap(), guesses);
+
+        db.store_guesses(&session_id, &guesses[..1]).unwrap();
+        assert_eq!(db.get_guesses(&session_id).unwrap().len(), 1);
+        assert!(db.get_guesses(&Uuid::new_v4()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_active_session_round_trip() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let now = chrono::Utc::now();
+        let mut active = ActiveSession {
+            session_id: Uuid::new_v4(),
+            start_date: now,
+            updated_date: now,
+            session_type: "Interactive".to_string(),
+            current_guess: "crane".to_string(),
+            strategy: "positional-frequency".to_string(),
+            seed: Some(42),
+            profile: DEFAULT_PROFILE.to_string(),
+        };
+        db.save_active_session(&active).unwrap();
+        active.current_guess = "slate".to_string();
+        db.save_active_session(&active).unwrap();
+
+        let saved = db
+            .get_active_sessions(now - chrono::Duration::hours(1), DEFAULT_PROFILE)
+            .unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].current_guess, "slate");
+        assert_eq!(saved[0].seed, Some(42));
+        assert!(
+            db.get_active_sessions(now + chrono::Duration::hours(1), DEFAULT_PROFILE)
+                .unwrap()
+                .is_empty()
+        );
+
+        db.delete_active_session(&active.session_id).unwrap();
+        assert!(
+            db.get_active_sessions(now - chrono::Duration::hours(1), DEFAULT_PROFILE)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_word_source_round_trip() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let source = WordSource {
+            name: "small".to_string(),
+            path: "/tmp/small.txt".to_string(),
+            source_hash: "abc".to_string(),
+            word_count: 2,
+            added_date: chrono::Utc::now(),
+        };
+        let words = vec![
+            Word::new(0, 2.5, "crane").unwrap(),
+            Word::new(0, 1.5, "slate").unwrap(),
+        ];
+        db.store_word_source(&source, &words).unwrap();
+        db.store_word_source(&source, &words).unwrap();
+
+        assert_eq!(db.get_word_sources().unwrap().len(), 1);
+        let word_db = db.load_word_source("small").unwrap().unwrap();
+        let top = word_db.get_top_words(1).unwrap();
+        assert_eq!(top[0].as_str(), "crane");
+        assert_eq!(top[0].total_probability, 2.5);
+        assert!(db.load_word_source("missing").unwrap().is_none());
+
+        db.delete_word_source("small").unwrap();
+        assert!(db.get_word_sources().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_past_answers() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let mut lost = sample_results("Interactive", 1, false);
+        lost.word = "a_p_e".to_string();
+        db.store_session_results(&lost).unwrap();
+        db.store_session_results(&sample_results("Interactive", 2, true))
+            .unwrap();
+        db.store_session_results(&sample_results("Test", 3, true))
+            .unwrap();
+
+        let from_sessions = db.get_known_session_answers().unwrap();
+        assert_eq!(
+            from_sessions,
+            vec![PastAnswer {
+                word: "crane".to_string(),
+                date: chrono::NaiveDate::from_ymd_opt(2025, 7, 2),
+            }]
+        );
+        assert_eq!(
+            db.store_past_answers(&from_sessions, "sessions").unwrap(),
+            1
+        );
+
+        let undated = PastAnswer {
+            word: "crane".to_string(),
+            date: None,
+        };
+        let other = PastAnswer {
+            word: "slate".to_string(),
+            date: None,
+        };
+        assert_eq!(db.store_past_answers(&[undated, other], "file").unwrap(), 1);
+        assert_eq!(
+            db.get_past_answers().unwrap(),
+            HashSet::from(["crane".to_string(), "slate".to_string()])
+        );
+        let date: Option<String> = db
+            .conn
+            .query_row(
+                "SELECT answer_date FROM past_answers WHERE word = 'crane'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(date.as_deref(), Some("2025-07-02"));
+
+        db.clear_past_answers().unwrap();
+        assert!(db.get_past_answers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archive_summaries_by_version() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let runs = [
+            ("0.1.0", 1, true, 4),
+            ("0.1.0", 2, false, 6),
+            ("0.2.0", 1, true, 3),
+            ("0.2.0", 9, true, 2),
+        ];
+        for (version, puzzle, win, guesses) in runs {
+            let mut results = sample_results("Archive", 1, win);
+            results.number_of_guesses = guesses;
+            db.store_session_results(&results).unwrap();
+            db.store_archive_run(&results.session_id, puzzle, "crane", version)
+                .unwrap();
+        }
+
+        // Puzzle 2 falls between the selected puzzles but was not picked, so it is left out.
+        let summaries = db.get_archive_summaries(&[1, 9]).unwrap();
+        assert_eq!(
+            summaries,
+            vec![
+                ArchiveRunSummary {
+                    crackle_version: "0.1.0".to_string(),
+                    runs: 1,
+                    wins: 1,
+                    average_guesses: Some(4.0),
+                },
+                ArchiveRunSummary {
+                    crackle_version: "0.2.0".to_string(),
+                    runs: 2,
+                    wins: 2,
+                    average_guesses: Some(2.5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_benchmark_run_round_trip() {
+        let db = empty_db();
+        db.migrate().unwrap();
+        let run = BenchmarkRun {
+            id: None,
+            created: sample_results("Test", 1, true).start_date,
+            crackle_version: "1.0.0".to_string(),
+            strategy: "positional-frequency".to_string(),
+            word_source: "builtin".to_string(),
+            word_source_hash: "hash".to_string(),
+            subset: Some("hard.txt".to_string()),
+            seed: u64::MAX,
+            opener: "crane".to_string(),
+            max_guesses: 6,
+            results: vec![
+                WordResult {
+                    word: "slate".to_string(),
+                    outcome: SessionOutcome::Won,
+                    guesses: 3,
+                },
+                WordResult {
+                    word: "fight".to_string(),
+                    outcome: SessionOutcome::Lost,
+                    guesses: 6,
+                },
+            ],
+        };
+
+        let id = db.store_benchmark_run(&run).unwrap();
+        let stored = db.get_benchmark_run(id).unwrap().unwrap();
+        assert_eq!(stored.id, Some(id));
+        assert_eq!(stored.seed, u64::MAX);
+        assert_eq!(stored.subset.as_deref(), Some("hard.txt"));
+        assert_eq!(stored.results[0].word, "fight");
+        assert_eq!(stored.results[0].outcome, SessionOutcome::Lost);
+        assert_eq!(stored.average_guesses(), Some(3.0));
+        assert!(db.get_benchmark_run(id + 1).unwrap().is_none());
+
+        assert!(db.get_benchmark_baseline("main").unwrap().is_none());
+        db.save_benchmark_baseline("main", id).unwrap();
+        let second = db.store_benchmark_run(&run).unwrap();
+        db.save_benchmark_baseline("main", second).unwrap();
+        assert_eq!(
+            db.get_benchmark_baseline("main").unwrap().unwrap().id,
+            Some(second)
+        );
+    }
+
+    #[test]
+    fn test_migrate_refuses_newer_database() {
+        let db = empty_db();
+        db.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            db.migrate(),
+            Err(MigrationError::UnsupportedVersion { .. })
+        ));
+    }
+}
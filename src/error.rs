@@ -32,6 +32,15 @@ pub enum RecoverableError {
 
     #[error("No words match current constraints")]
     NoMatchingWords,
+
+    #[error("'{answer}' would have given '{expected}' for '{guess}', not '{feedback}'")]
+    AnswerMismatch {
+        answer: String,
+        guess: String,
+        feedback: String,
+        expected: String,
+    },
+
     #[error("Network timeout occurred")]
     NetworkTimeout,
 }
@@ -0,0 +1,398 @@
+use crate::config::DEFAULT_PROFILE;
+use crate::database::{DB, SessionFilter};
+use crate::session::{GuessRecord, SessionOutcome, SessionResults};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Database error: {0}")]
+    Rusqlite(#[from] rusqlite::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported file format '{0}', expected .csv or .json")]
+    UnsupportedFormat(String),
+    #[error("Invalid record for session '{0}': {1}")]
+    InvalidRecord(String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// Picks the format from the file extension.
+    pub fn from_path(path: &Path) -> Result<Self, ExportError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(ExportError::UnsupportedFormat(extension)),
+        }
+    }
+}
+
+/// A stored session together with its guess history, as written to JSON exports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub session_type: String,
+    pub word: String,
+    pub number_of_guesses: u8,
+    pub win: bool,
+    #[serde(default = "default_profile")]
+    pub profile: String,
+    /// How the game ended. Exports written before outcomes were stored leave it out, and `win` decides it.
+    #[serde(default)]
+    pub status: Option<SessionOutcome>,
+    /// Seed of the session's random choices, when it was recorded.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub guesses: Vec<GuessRecord>,
+}
+
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+/// Flat form of `SessionRecord` for CSV, with the history written as space separated `guess:feedback` pairs.
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRecord {
+    session_id: String,
+    start_date: String,
+    end_date: String,
+    session_type: String,
+    word: String,
+    number_of_guesses: u8,
+    win: bool,
+    #[serde(default = "default_profile")]
+    profile: String,
+    #[serde(default)]
+    status: Option<SessionOutcome>,
+    #[serde(default)]
+    seed: Option<u64>,
+    guesses: String,
+}
+
+/// Counts of sessions handled by an import.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+impl SessionRecord {
+    fn new(results: SessionResults, guesses: Vec<GuessRecord>) -> Self {
+        SessionRecord {
+            session_id: results.session_id.to_string(),
+            start_date: results.start_date.to_rfc3339(),
+            end_date: results.end_date.to_rfc3339(),
+            session_type: results.session_type,
+            word: results.word,
+            number_of_guesses: results.number_of_guesses,
+            win: results.win,
+            profile: results.profile,
+            status: Some(results.outcome),
+            seed: results.seed,
+            guesses,
+        }
+    }
+
+    fn to_session_results(&self) -> Result<(Uuid, SessionResults), ExportError> {
+        let invalid = |msg: String| ExportError::InvalidRecord(self.session_id.clone(), msg);
+        let parse_date = |text: &str| {
+            chrono::DateTime::parse_from_rfc3339(text)
+                .map(|d| d.with_timezone(&chrono::Utc))
+                .map_err(|e| invalid(e.to_string()))
+        };
+        let session_id = Uuid::parse_str(&self.session_id).map_err(|e| invalid(e.to_string()))?;
+
+        Ok((
+            session_id,
+            SessionResults {
+                session_id,
+                start_date: parse_date(&self.start_date)?,
+                end_date: parse_date(&self.end_date)?,
+                session_type: self.session_type.clone(),
+                word: self.word.clone(),
+                number_of_guesses: self.number_of_guesses,
+                win: self.win,
+                profile: self.profile.clone(),
+                outcome: self.status.unwrap_or(if self.win {
+                    SessionOutcome::Won
+                } else {
+                    SessionOutcome::Lost
+                }),
+                seed: self.seed,
+            },
+        ))
+    }
+}
+
+impl From<SessionRecord> for CsvRecord {
+    fn from(record: SessionRecord) -> Self {
+        let guesses = record
+            .guesses
+            .iter()
+            .map(|g| format!("{}:{}", g.guess, g.feedback))
+            .collect::<Vec<_>>()
+            .join(" ");
+        CsvRecord {
+            session_id: record.session_id,
+            start_date: record.start_date,
+            end_date: record.end_date,
+            session_type: record.session_type,
+            word: record.word,
+            number_of_guesses: record.number_of_guesses,
+            win: record.win,
+            profile: record.profile,
+            status: record.status,
+            seed: record.seed,
+            guesses,
+        }
+    }
+}
+
+impl TryFrom<CsvRecord> for SessionRecord {
+    type Error = ExportError;
+
+    fn try_from(record: CsvRecord) -> Result<Self, Self::Error> {
+        let guesses = record
+            .guesses
+            .split_whitespace()
+            .map(|pair| match pair.split_once(':') {
+                Some((guess, feedback)) => Ok(GuessRecord {
+                    guess: guess.to_string(),
+                    feedback: feedback.to_string(),
+                }),
+                None => Err(ExportError::InvalidRecord(
+                    record.session_id.clone(),
+                    format!("malformed guess '{pair}'"),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SessionRecord {
+            session_id: record.session_id,
+            start_date: record.start_date,
+            end_date: record.end_date,
+            session_type: record.session_type,
+            word: record.word,
+            number_of_guesses: record.number_of_guesses,
+            win: record.win,
+            profile: record.profile,
+            status: record.status,
+            seed: record.seed,
+            guesses,
+        })
+    }
+}
+
+/// Reads the sessions matching the filter along with any stored guess history.
+pub fn collect_records(db: &DB, filter: &SessionFilter) -> Result<Vec<SessionRecord>, ExportError> {
+    db.get_session_results(filter)?
+        .into_iter()
+        .map(|results| {
+            let guesses = db.get_guesses(&results.session_id)?;
+            Ok(SessionRecord::new(results, guesses))
+        })
+        .collect()
+}
+
+/// Exports the sessions matching the filter to `path`, choosing CSV or JSON from the file extension.
+/// Returns the number of sessions written.
+pub fn export_sessions(db: &DB, filter: &SessionFilter, path: &Path) -> Result<usize, ExportError> {
+    let format = ExportFormat::from_path(path)?;
+    let records = collect_records(db, filter)?;
+    let count = records.len();
+    let file = std::fs::File::create(path)?;
+    write_records(&records, format, file)?;
+    Ok(count)
+}
+
+/// Imports sessions from a CSV or JSON export into `db`. Sessions whose id is already stored are skipped.
+pub fn import_sessions(db: &DB, path: &Path) -> Result<ImportSummary, ExportError> {
+    let format = ExportFormat::from_path(path)?;
+    let file = std::fs::File::open(path)?;
+    let records = read_records(format, file)?;
+    merge_records(db, &records)
+}
+
+pub fn write_records<W: std::io::Write>(
+    records: &[SessionRecord],
+    format: ExportFormat,
+    writer: W,
+) -> Result<(), ExportError> {
+    match format {
+        ExportFormat::Json => serde_json::to_writer_pretty(writer, records)?,
+        ExportFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for record in records {
+                csv_writer.serialize(CsvRecord::from(record.clone()))?;
+            }
+            csv_writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+pub fn read_records<R: std::io::Read>(
+    format: ExportFormat,
+    reader: R,
+) -> Result<Vec<SessionRecord>, ExportError> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::from_reader(reader)?),
+        ExportFormat::Csv => csv::Reader::from_reader(reader)
+            .deserialize::<CsvRecord>()
+            .map(|record| SessionRecord::try_from(record?))
+            .collect(),
+    }
+}
+
+/// Stores records that are not already in the database, deduplicating on `session_id`.
+/// The import runs in one transaction, so a bad record leaves the database as it was.
+pub fn merge_records(db: &DB, records: &[SessionRecord]) -> Result<ImportSummary, ExportError> {
+    db.transaction(|db| {
+        let mut summary = ImportSummary::default();
+        for record in records {
+            let (session_id, results) = record.to_session_results()?;
+            if db.session_exists(&record.session_id)? {
+                summary.skipped += 1;
+                continue;
+            }
+            db.store_session_results(&results)?;
+            db.store_guesses(&session_id, &record.guesses)?;
+            summary.imported += 1;
+        }
+        Ok(summary)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(word: &str) -> SessionRecord {
+        SessionRecord {
+            session_id: Uuid::new_v4().to_string(),
+            start_date: "2025-07-31T19:29:57+00:00".to_string(),
+            end_date: "2025-07-31T19:31:02+00:00".to_string(),
+            session_type: "Test".to_string(),
+            word: word.to_string(),
+            number_of_guesses: 2,
+            win: true,
+            profile: "default".to_string(),
+            status: Some(SessionOutcome::Won),
+            seed: Some(42),
+            guesses: vec![
+                GuessRecord {
+                    guess: "crane".to_string(),
+                    feedback: "nnygy".to_string(),
+                },
+                GuessRecord {
+                    guess: word.to_string(),
+                    feedback: "ggggg".to_string(),
+                },
+            ],
+        }
+    }
+
+    fn round_trip(format: ExportFormat, records: &[SessionRecord]) -> Vec<SessionRecord> {
+        let mut buffer = Vec::new();
+        write_records(records, format, &mut buffer).unwrap();
+        read_records(format, buffer.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.CSV")).unwrap(),
+            ExportFormat::Csv
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.json")).unwrap(),
+            ExportFormat::Json
+        );
+        assert!(ExportFormat::from_path(Path::new("out.txt")).is_err());
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let records = vec![record("slate"), record("adieu")];
+        assert_eq!(round_trip(ExportFormat::Csv, &records), records);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let records = vec![record("slate")];
+        assert_eq!(round_trip(ExportFormat::Json, &records), records);
+    }
+
+    #[test]
+    fn test_records_without_status_use_win() {
+        let csv = "session_id,start_date,end_date,session_type,word,number_of_guesses,win,profile,guesses\n\
+            8f0e5c1e-7a0b-4b7a-9a51-6f6d5e9b2c11,2025-07-31T19:29:57+00:00,2025-07-31T19:31:02+00:00,Test,slate,6,false,default,\n";
+        let records = read_records(ExportFormat::Csv, csv.as_bytes()).unwrap();
+        assert_eq!(records[0].status, None);
+        let (_, results) = records[0].to_session_results().unwrap();
+        assert_eq!(results.outcome, SessionOutcome::Lost);
+    }
+
+    #[test]
+    fn test_merge_dedupes_on_session_id() {
+        let db = DB::new_results_in_memory().unwrap();
+        let records = vec![record("slate"), record("adieu")];
+
+        let first = merge_records(&db, &records).unwrap();
+        assert_eq!(first.imported, 2);
+
+        let second = merge_records(&db, &records).unwrap();
+        assert_eq!(
+            second,
+            ImportSummary {
+                imported: 0,
+                skipped: 2
+            }
+        );
+
+        let exported = collect_records(&db, &SessionFilter::default()).unwrap();
+        assert_eq!(exported.len(), 2);
+        assert_eq!(exported[0].guesses.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_leaves_db_unchanged_on_bad_record() {
+        let db = DB::new_results_in_memory().unwrap();
+        let mut bad = record("adieu");
+        bad.end_date = "yesterday".to_string();
+        let records = vec![record("slate"), bad, record("crane")];
+
+        assert!(matches!(
+            merge_records(&db, &records),
+            Err(ExportError::InvalidRecord(..))
+        ));
+        assert!(
+            collect_records(&db, &SessionFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!db.session_exists(&records[0].session_id).unwrap());
+
+        let retried = merge_records(&db, &[records[0].clone(), records[2].clone()]).unwrap();
+        assert_eq!(retried.imported, 2);
+    }
+}
@@ -1,10 +1,12 @@
+use crate::config::PastAnswerMode;
 use crate::error::RecoverableError;
 use crate::filter_logic;
-use crate::word_analyzer::WordAnalyzer;
+use crate::word_analyzer::{Word, WordAnalyzer};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::Arc;
 // the game engine, manages game state and logic for the game
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GameEngine {
     excluded_characters: HashMap<char, bool>,
     // uses a key of character + position
@@ -13,6 +15,11 @@ pub struct GameEngine {
     yellow_characters: HashMap<char, bool>,
     answer: [char; 5],
     current_guess: String,
+    // previously used official answers, treated according to past_answer_mode when picking a guess.
+    // Shared, so cloning an engine mid-game stays cheap.
+    past_answers: Arc<HashSet<String>>,
+    past_answer_mode: PastAnswerMode,
+    past_answer_weight: f64,
 }
 impl Default for GameEngine {
     fn default() -> Self {
@@ -22,6 +29,9 @@ impl Default for GameEngine {
             yellow_characters: HashMap::new(),
             answer: ['_'; 5],
             current_guess: String::new(),
+            past_answers: Arc::default(),
+            past_answer_mode: PastAnswerMode::Ignore,
+            past_answer_weight: 1.0,
         }
     }
 }
@@ -41,9 +51,35 @@ impl GameEngine {
             yellow_characters: HashMap::new(),
             answer: ['_'; 5],
             current_guess: String::new(),
+            past_answers: Arc::default(),
+            past_answer_mode: PastAnswerMode::Ignore,
+            past_answer_weight: 1.0,
         }
     }
 
+    /// Sets the previously used official answers and how they should be treated when picking the next guess.
+    /// Past answers always stay in the candidate pool, so they still shape the probabilities and can be guessed as a last resort.
+    pub fn set_past_answers(
+        &mut self,
+        past_answers: HashSet<String>,
+        mode: PastAnswerMode,
+        weight: f64,
+    ) {
+        self.past_answers = Arc::new(past_answers);
+        self.past_answer_mode = mode;
+        self.past_answer_weight = weight;
+    }
+
+    /// Rebuilds the engine state by replaying each guess with the `gyngy` feedback it received, in order.
+    pub fn from_history<'h>(history: impl IntoIterator<Item = (&'h str, &'h str)>) -> Self {
+        let mut engine = GameEngine::new();
+        for (guess, feedback) in history {
+            engine.set_starting_word(guess.to_string());
+            engine.parse_input(feedback);
+        }
+        engine
+    }
+
     /// Sets the starting word for the engine to use for the first user input.
     pub fn set_starting_word(&mut self, starting_word: String) {
         self.current_guess = starting_word;
@@ -101,6 +137,24 @@ impl GameEngine {
         &mut self,
         possible_words: Vec<String>,
     ) -> Result<String, RecoverableError> {
+        let candidates = self.rank_candidates(possible_words);
+        self.choose_guess(&candidates)
+    }
+
+    /// Picks the next guess from candidates already ranked by `rank_candidates` and makes it the current guess.
+    pub fn choose_guess(&mut self, candidates: &[Word]) -> Result<String, RecoverableError> {
+        let next_guess = self.pick_candidate(candidates);
+        match next_guess {
+            Some(word) => {
+                self.current_guess = word.to_string();
+                Ok(word.to_string())
+            }
+            None => Err(RecoverableError::NoGuessFound),
+        }
+    }
+
+    /// Filters the possible words down to those matching the current constraints and scores them against each other, most probable first.
+    pub fn rank_candidates(&self, possible_words: Vec<String>) -> Vec<Word> {
         let filtered_words = filter_logic::filter_potential_words(
             possible_words,
             &self.yellow_positions,
@@ -113,16 +167,36 @@ impl GameEngine {
             let _result = word_analyzer.analyze_word(&word);
         }
         word_analyzer.finalize_probabilities();
-        let next_guess = word_analyzer
-            .get_most_probable_word()
-            .map(|word| word.as_str());
-        match next_guess {
-            Some(word) => {
-                self.current_guess = word.to_string();
-                Ok(word.to_string())
+
+        let mut candidates = word_analyzer.words().clone();
+        // A stable sort keeps equally probable words in list order, so ties resolve the same way as `pick_candidate`.
+        candidates.sort_by(|a, b| b.total_probability.total_cmp(&a.total_probability));
+        candidates
+    }
+
+    /// Picks the most probable candidate after applying the past answer mode. On ties the first candidate wins.
+    fn pick_candidate(&self, words: &[Word]) -> Option<String> {
+        let is_past = |word: &Word| self.past_answers.contains(&word.as_str());
+        let score = |word: &Word| match self.past_answer_mode {
+            PastAnswerMode::DownWeight if is_past(word) => {
+                word.total_probability * self.past_answer_weight
             }
-            None => Err(RecoverableError::NoGuessFound),
+            _ => word.total_probability,
+        };
+
+        let mut pool: Vec<&Word> = words.iter().collect();
+        if self.past_answer_mode == PastAnswerMode::Drop && pool.iter().any(|w| !is_past(w)) {
+            pool.retain(|w| !is_past(w));
         }
+
+        let mut best: Option<(&Word, f64)> = None;
+        for word in pool {
+            let word_score = score(word);
+            if best.is_none_or(|(_, best_score)| word_score > best_score) {
+                best = Some((word, word_score));
+            }
+        }
+        best.map(|(word, _)| word.as_str())
     }
 
     /// Returns the current state of the answer to the caller to be used to query the database.
@@ -136,6 +210,43 @@ impl GameEngine {
     }
 }
 
+/// Scores a guess against a known answer the way Wordle does, returning feedback in `gyngy` format.
+/// Repeated letters are only marked yellow as many times as they appear in the answer outside of green positions.
+/// Returns `None` when the words differ in length, as there is no feedback to give.
+pub fn score_guess(guess: &str, answer: &str) -> Option<String> {
+    let guess_chars: Vec<char> = guess.chars().collect();
+    let secret_chars: Vec<char> = answer.chars().collect();
+    if guess_chars.len() != secret_chars.len() {
+        return None;
+    }
+
+    let len = guess_chars.len();
+    let mut result = vec!['n'; len];
+    // Answer letters that are not green, counted so each can only turn one guess letter yellow.
+    let mut secret_char_counts = HashMap::new();
+    for i in 0..len {
+        if guess_chars[i] == secret_chars[i] {
+            result[i] = 'g';
+        } else {
+            *secret_char_counts.entry(secret_chars[i]).or_insert(0) += 1;
+        }
+    }
+
+    for i in 0..len {
+        if result[i] == 'g' {
+            continue;
+        }
+        if let Some(count) = secret_char_counts.get_mut(&guess_chars[i])
+            && *count > 0
+        {
+            result[i] = 'y';
+            *count -= 1;
+        }
+    }
+
+    Some(result.into_iter().collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,6 +257,74 @@ mod tests {
         engine
     }
 
+    #[test]
+    fn test_from_history_matches_step_by_step() {
+        let mut engine = create_test_engine("crane");
+        engine.parse_input("nnygy");
+        engine.set_starting_word(String::from("slate"));
+        engine.parse_input("ngnny");
+
+        let replayed = GameEngine::from_history([("crane", "nnygy"), ("slate", "ngnny")]);
+        assert_eq!(replayed.answer, engine.answer);
+        assert_eq!(replayed.excluded_characters, engine.excluded_characters);
+        assert_eq!(replayed.yellow_positions, engine.yellow_positions);
+        assert_eq!(replayed.yellow_characters, engine.yellow_characters);
+        assert_eq!(replayed.get_current_guess(), "slate");
+    }
+
+    #[test]
+    fn test_score_guess() {
+        assert_eq!(score_guess("crane", "crane").as_deref(), Some("ggggg"));
+        assert_eq!(score_guess("crane", "slate").as_deref(), Some("nngng"));
+        // Only one 'e' in the answer, so the second 'e' in the guess is gray.
+        assert_eq!(score_guess("geese", "those").as_deref(), Some("nnngg"));
+        assert_eq!(score_guess("speed", "abide").as_deref(), Some("nnyny"));
+        assert_eq!(score_guess("crane", "toolong"), None);
+    }
+
+    fn past_answer_engine(mode: PastAnswerMode) -> GameEngine {
+        let mut engine = create_test_engine("zzzzz");
+        let past_answers = HashSet::from(["short".to_string()]);
+        engine.set_past_answers(past_answers, mode, 0.5);
+        engine
+    }
+
+    fn candidates() -> Vec<String> {
+        ["smart", "start", "sport", "shirt", "short"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_past_answers_ignored_by_default() {
+        let mut engine = past_answer_engine(PastAnswerMode::Ignore);
+        assert_eq!(engine.get_next_guess(candidates()).unwrap(), "short");
+    }
+
+    #[test]
+    fn test_past_answers_dropped() {
+        let mut engine = past_answer_engine(PastAnswerMode::Drop);
+        assert_ne!(engine.get_next_guess(candidates()).unwrap(), "short");
+
+        // A past answer is still guessed when it is the only candidate left.
+        let mut engine = past_answer_engine(PastAnswerMode::Drop);
+        assert_eq!(
+            engine.get_next_guess(vec!["short".to_string()]).unwrap(),
+            "short"
+        );
+    }
+
+    #[test]
+    fn test_past_answers_down_weighted() {
+        let mut engine = past_answer_engine(PastAnswerMode::DownWeight);
+        assert_ne!(engine.get_next_guess(candidates()).unwrap(), "short");
+
+        let mut engine = past_answer_engine(PastAnswerMode::DownWeight);
+        engine.past_answer_weight = 1.0;
+        assert_eq!(engine.get_next_guess(candidates()).unwrap(), "short");
+    }
+
     #[test]
     fn test_check_for_win() {
         let mut engine = create_test_engine("apple");
@@ -1,24 +1,109 @@
-use std::io::Read;
+use std::io::{BufRead, Write};
 
+use super::check_input;
 use crate::input::InputSource;
-#[allow(dead_code)]
-pub struct ApiInput<R: Read> {
+use crate::protocol::{ClientMessage, ProtocolError, ServerMessage, read_message, write_message};
+
+/// Reads JSON-lines protocol messages. Requests for input and rejected messages are written back to the client.
+pub struct ApiInput<R: BufRead, W: Write> {
     reader: R,
+    writer: W,
 }
 
-impl<R: Read> InputSource for ApiInput<R> {
-    fn get_feedback(&mut self) -> Result<String, std::io::Error> {
-        unimplemented!()
+impl<R: BufRead, W: Write> ApiInput<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self { reader, writer }
     }
 
-    fn has_next_game(&self) -> bool {
-        unimplemented!()
+    /// Reads messages until `accept` takes one, replying with a non-fatal error to anything else.
+    /// `None` means the client sent `quit` or closed its input.
+    fn read_until<T>(
+        &mut self,
+        expected: &str,
+        mut accept: impl FnMut(ClientMessage) -> Result<T, ClientMessage>,
+    ) -> Result<Option<T>, std::io::Error> {
+        loop {
+            let error = match read_message(&mut self.reader) {
+                Ok(None) | Ok(Some(ClientMessage::Quit)) => return Ok(None),
+                Ok(Some(message)) => match accept(message) {
+                    Ok(value) => return Ok(Some(value)),
+                    Err(message) => format!("Expected {expected}, got {message:?}"),
+                },
+                Err(ProtocolError::Io(e)) => return Err(e),
+                Err(e) => e.to_string(),
+            };
+            self.send_error(error)?;
+        }
+    }
+
+    fn send_error(&mut self, message: String) -> Result<(), std::io::Error> {
+        write_message(
+            &mut self.writer,
+            &ServerMessage::Error {
+                message,
+                fatal: false,
+            },
+        )
+    }
+}
+
+/// The error for a client that leaves while the game waits for its answer.
+fn client_left() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        "Client left before the game ended",
+    )
+}
+
+impl<R: BufRead, W: Write> InputSource for ApiInput<R, W> {
+    fn get_feedback(&mut self) -> Result<Option<String>, std::io::Error> {
+        loop {
+            let Some(feedback) = self.read_until("feedback", |message| match message {
+                ClientMessage::Feedback { feedback } => Ok(feedback.trim().to_lowercase()),
+                other => Err(other),
+            })?
+            else {
+                return Ok(None);
+            };
+            match check_input(&feedback) {
+                Ok(_) => return Ok(Some(feedback)),
+                Err(e) => self.send_error(format!("Invalid input: {e}"))?,
+            }
+        }
+    }
+
+    fn get_answer(&mut self) -> Result<Option<String>, std::io::Error> {
+        write_message(&mut self.writer, &ServerMessage::AnswerRequest)?;
+        self.read_until("answer", |message| match message {
+            ClientMessage::Answer { answer } => Ok(answer
+                .map(|answer| answer.trim().to_lowercase())
+                .filter(|answer| !answer.is_empty())),
+            other => Err(other),
+        })?
+        .ok_or_else(client_left)
+    }
+
+    fn confirm_add_word(&mut self, word: &str) -> Result<bool, std::io::Error> {
+        write_message(
+            &mut self.writer,
+            &ServerMessage::AddWordRequest {
+                word: word.to_string(),
+            },
+        )?;
+        self.read_until("add_word", |message| match message {
+            ClientMessage::AddWord { add } => Ok(add),
+            other => Err(other),
+        })?
+        .ok_or_else(client_left)
     }
 
-    fn next_game(&self) {
-        unimplemented!()
+    /// Games are started by `new_game` messages, which `protocol::serve` reads between sessions.
+    fn has_next_game(&self) -> bool {
+        false
     }
 
+    fn next_game(&self) {}
+
     fn is_interactive(&self) -> bool {
         false
     }
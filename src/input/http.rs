@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::input::InputSource;
+
+/// Answers an HTTP client sent along with its feedback, used if the game ends on that turn.
+#[derive(Debug, Default)]
+pub struct HttpReply {
+    pub answer: Option<String>,
+    pub add_word: bool,
+}
+
+/// Input for games played over the HTTP service. Feedback is pushed with `Session::submit_feedback`,
+/// so this only hands over the answer and add-word choice that came with the last request.
+pub struct HttpInput {
+    reply: Rc<RefCell<HttpReply>>,
+}
+
+impl HttpInput {
+    pub fn new(reply: Rc<RefCell<HttpReply>>) -> Self {
+        Self { reply }
+    }
+}
+
+impl InputSource for HttpInput {
+    fn get_feedback(&mut self) -> Result<Option<String>, std::io::Error> {
+        Err(std::io::Error::other(
+            "HTTP games receive feedback through submit_feedback",
+        ))
+    }
+
+    /// Gives out the answer once, so a rejected answer ends the game as unknown instead of being retried.
+    fn get_answer(&mut self) -> Result<Option<String>, std::io::Error> {
+        Ok(self.reply.borrow_mut().answer.take())
+    }
+
+    fn confirm_add_word(&mut self, _word: &str) -> Result<bool, std::io::Error> {
+        Ok(self.reply.borrow().add_word)
+    }
+
+    fn has_next_game(&self) -> bool {
+        false
+    }
+
+    fn next_game(&self) {}
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
@@ -12,20 +12,23 @@ impl<R: BufRead> InteractiveInput<R> {
 }
 
 impl<R: BufRead> InputSource for InteractiveInput<R> {
-    fn get_feedback(&mut self) -> Result<String, std::io::Error> {
+    fn get_feedback(&mut self) -> Result<Option<String>, std::io::Error> {
         loop {
             let mut input = String::new();
-            self.reader.read_line(&mut input)?;
+            // End of input leaves the game the same way as typing `exit`
+            if self.reader.read_line(&mut input)? == 0 {
+                return Ok(None);
+            }
 
             let input = input.trim().to_lowercase();
             if input == "exit" {
                 println!("Exiting session");
-                return Err(std::io::Error::other("Exiting game"));
+                return Ok(None);
             }
 
             let input_ok = check_input(&input);
             match input_ok {
-                Ok(_) => return Ok(input),
+                Ok(_) => return Ok(Some(input)),
                 Err(e) => {
                     println!("Invalid input: {e}. Please try again.");
                     continue;
@@ -34,6 +37,27 @@ impl<R: BufRead> InputSource for InteractiveInput<R> {
         }
     }
 
+    fn get_answer(&mut self) -> Result<Option<String>, std::io::Error> {
+        println!("What was the answer? (press enter to skip)");
+        let mut input = String::new();
+        self.reader.read_line(&mut input)?;
+
+        let input = input.trim().to_lowercase();
+        if input.is_empty() || input == "exit" {
+            return Ok(None);
+        }
+        Ok(Some(input))
+    }
+
+    fn confirm_add_word(&mut self, word: &str) -> Result<bool, std::io::Error> {
+        println!("'{word}' is not in the word list. Add it to your custom word list? (y/n)");
+        let mut input = String::new();
+        self.reader.read_line(&mut input)?;
+
+        let input = input.trim().to_lowercase();
+        Ok(input == "y" || input == "yes")
+    }
+
     fn has_next_game(&self) -> bool {
         unimplemented!()
     }
@@ -1,20 +1,30 @@
 mod api;
+mod http;
 mod interactive;
 mod test;
+mod tui;
 use crate::constants::WORD_LENGTH;
 use crate::error::RecoverableError;
 pub use api::ApiInput;
+pub use http::{HttpInput, HttpReply};
 pub use interactive::InteractiveInput;
 pub use test::TestInput;
+pub use tui::TuiInput;
 
 pub trait InputSource {
-    fn get_feedback(&mut self) -> Result<String, std::io::Error>;
+    /// Reads feedback on the current guess. `None` means the player left the game.
+    fn get_feedback(&mut self) -> Result<Option<String>, std::io::Error>;
+    /// Asks for the real answer after a lost or stumped game. `None` means the answer is not known.
+    fn get_answer(&mut self) -> Result<Option<String>, std::io::Error>;
+    /// Asks whether an answer that is missing from the word list should be added to the custom word list.
+    fn confirm_add_word(&mut self, word: &str) -> Result<bool, std::io::Error>;
     fn has_next_game(&self) -> bool;
     fn next_game(&self);
     fn is_interactive(&self) -> bool;
 }
 
-fn check_input(input: &str) -> Result<(), RecoverableError> {
+/// Checks that feedback is five characters of `g`, `y` or `n`.
+pub(crate) fn check_input(input: &str) -> Result<(), RecoverableError> {
     if input.len() != WORD_LENGTH {
         return Err(RecoverableError::InvalidWordLength(input.len()));
     }
@@ -1,7 +1,7 @@
+use crate::game_engine::score_guess;
 use crate::input::InputSource;
 use crate::shared_state::SharedTestState;
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::rc::Rc;
 
 #[allow(dead_code)]
@@ -20,10 +20,18 @@ impl TestInput {
 }
 
 impl InputSource for TestInput {
-    fn get_feedback(&mut self) -> Result<String, std::io::Error> {
+    fn get_feedback(&mut self) -> Result<Option<String>, std::io::Error> {
         let state = self.shared_state.borrow();
         let last_guess = state.guesses.last().unwrap();
-        Ok(parse_response(self, last_guess))
+        parse_response(self, last_guess).map(Some)
+    }
+
+    fn get_answer(&mut self) -> Result<Option<String>, std::io::Error> {
+        Ok(Some(self.random_word.clone()))
+    }
+
+    fn confirm_add_word(&mut self, _word: &str) -> Result<bool, std::io::Error> {
+        Ok(false)
     }
 
     fn has_next_game(&self) -> bool {
@@ -38,47 +46,14 @@ impl InputSource for TestInput {
         false
     }
 }
-fn parse_response(input: &TestInput, response: &str) -> String {
-    // Collect chars into vectors for easier indexing and manipulation.
-    let guess_chars: Vec<char> = response.chars().collect();
-    let secret_chars: Vec<char> = input.random_word.chars().collect();
-
-    // Ensure words are the same length, adjust as needed for your game's rules.
-    if guess_chars.len() != secret_chars.len() {
-        return String::new(); // Or handle error
-    }
-
-    let len = guess_chars.len();
-    let mut result = vec!['n'; len]; // Default to 'n' (gray/no)
-    let mut secret_char_counts = HashMap::new();
-
-    // --- First Pass: Find all "green" matches ('g') ---
-    // A character is green if it's the correct letter in the correct position.
-    for i in 0..len {
-        if guess_chars[i] == secret_chars[i] {
-            result[i] = 'g';
-        } else {
-            // Build a frequency map of the remaining secret characters.
-            *secret_char_counts.entry(secret_chars[i]).or_insert(0) += 1;
-        }
-    }
-
-    // --- Second Pass: Find all "yellow" matches ('y') ---
-    // A character is yellow if it exists in the word but is in the wrong position.
-    for i in 0..len {
-        // Skip letters that are already green.
-        if result[i] == 'g' {
-            continue;
-        }
-
-        // Check if this character exists in our map of remaining secret chars.
-        if let Some(count) = secret_char_counts.get_mut(&guess_chars[i]) {
-            if *count > 0 {
-                result[i] = 'y';
-                *count -= 1; // Decrement the count so it can't be used again.
-            }
-        }
-    }
-
-    result.into_iter().collect()
+fn parse_response(input: &TestInput, response: &str) -> Result<String, std::io::Error> {
+    score_guess(response, &input.random_word).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Cannot score '{response}' against '{}', they differ in length",
+                input.random_word
+            ),
+        )
+    })
 }
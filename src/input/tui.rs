@@ -0,0 +1,42 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::input::InputSource;
+use crate::tui::Tui;
+
+/// Reads feedback from the full-screen terminal UI, where tiles are coloured with the keyboard.
+pub struct TuiInput {
+    tui: Rc<RefCell<Tui>>,
+}
+
+impl TuiInput {
+    pub fn new(tui: Rc<RefCell<Tui>>) -> Self {
+        Self { tui }
+    }
+}
+
+impl InputSource for TuiInput {
+    fn get_feedback(&mut self) -> Result<Option<String>, std::io::Error> {
+        self.tui.borrow_mut().read_feedback()
+    }
+
+    fn get_answer(&mut self) -> Result<Option<String>, std::io::Error> {
+        self.tui.borrow_mut().read_answer()
+    }
+
+    fn confirm_add_word(&mut self, word: &str) -> Result<bool, std::io::Error> {
+        self.tui.borrow_mut().confirm(&format!(
+            "'{word}' is not in the word list. Add it to your custom word list?"
+        ))
+    }
+
+    fn has_next_game(&self) -> bool {
+        false
+    }
+
+    fn next_game(&self) {}
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
@@ -1,14 +1,26 @@
 pub mod database;
 pub mod setup;
 pub mod word_analyzer;
+pub mod word_list;
 pub use database::DB;
+pub mod archive;
+pub mod benchmark;
+pub mod cli;
 pub mod config;
 pub mod constants;
 pub mod error;
+pub mod export;
 pub mod filter_logic;
 pub mod game_engine;
 pub mod input;
 pub mod logs;
 pub mod output;
+pub mod past_answers;
+pub mod protocol;
+pub mod replay;
+pub mod report;
+pub mod server;
 pub mod session;
 pub mod shared_state;
+pub mod suggest;
+pub mod tui;
@@ -1,13 +1,220 @@
+use crate::config::{Config, LogLevel};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
 
-pub fn log_session_state(mut state: String) -> Result<(), std::io::Error> {
-    let pwd = std::env::current_dir().unwrap();
-    let file_path = pwd.join("logs.txt");
-    state.push('\n');
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_path)?;
-    file.write_all(state.as_bytes())?;
+/// Environment variable that overrides `Config::log_level`, e.g. `CRACKLE_LOG=debug`.
+pub const LOG_ENV: &str = "CRACKLE_LOG";
+
+/// Size at which the log file is rotated.
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Number of rotated files kept as `crackle.log.1` (newest) to `crackle.log.N`.
+pub const ROTATED_LOGS: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum LogError {
+    #[error("Could not open log file {path}: {source}")]
+    Open {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid {LOG_ENV} value '{0}', expected off, error, warn, info, debug or trace")]
+    InvalidLevel(String),
+}
+
+/// Appends log lines to a file, rotating it once it grows past `max_bytes`.
+pub struct FileLogger {
+    path: PathBuf,
+    level: LevelFilter,
+    max_bytes: u64,
+    keep: usize,
+    file: Mutex<Option<File>>,
+}
+
+impl FileLogger {
+    pub fn new(path: PathBuf, level: LevelFilter, max_bytes: u64, keep: usize) -> Self {
+        FileLogger {
+            path,
+            level,
+            max_bytes,
+            keep,
+            file: Mutex::new(None),
+        }
+    }
+
+    fn open(&self) -> std::io::Result<File> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+    }
+
+    /// Shifts `crackle.log.N-1` to `crackle.log.N` and so on, dropping the oldest file, then moves the live log to `.1`.
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+        for n in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                std::fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+
+    fn write_line(&self, line: &str) -> std::io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let too_big = match file.as_ref() {
+            Some(open) => open.metadata()?.len() >= self.max_bytes,
+            None => std::fs::metadata(&self.path).is_ok_and(|meta| meta.len() >= self.max_bytes),
+        };
+        if too_big {
+            *file = None;
+            self.rotate()?;
+        }
+        if file.is_none() {
+            *file = Some(self.open()?);
+        }
+        if let Some(open) = file.as_mut() {
+            writeln!(open, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        // Logging must never stop a game, so a log file that cannot be written is ignored.
+        let _ = self.write_line(&line);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// The level to log at: `CRACKLE_LOG` when it is set, otherwise `Config::log_level`.
+pub fn resolve_level(config: &Config, env: Option<&str>) -> Result<LevelFilter, LogError> {
+    let level = match env.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => value
+            .parse::<LogLevel>()
+            .map_err(|_| LogError::InvalidLevel(value.to_string()))?,
+        None => config.log_level,
+    };
+    Ok(level.filter())
+}
+
+/// Installs the file logger for this process, writing to `Config::log_path`.
+/// Calling it again after a logger is installed does nothing.
+pub fn init(config: &Config) -> Result<(), LogError> {
+    let level = resolve_level(config, std::env::var(LOG_ENV).ok().as_deref())?;
+    if level == LevelFilter::Off {
+        return Ok(());
+    }
+    let logger = FileLogger::new(config.log_path(), level, MAX_LOG_BYTES, ROTATED_LOGS);
+    // Open the file up front so a bad path is reported once instead of silently dropping every line.
+    let file = logger.open().map_err(|source| LogError::Open {
+        path: config.log_path(),
+        source,
+    })?;
+    *logger.file.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crackle-logs-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("crackle.log")
+    }
+
+    fn log_at(logger: &FileLogger, level: Level, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target("crackle::session")
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_levels_filter_lines() {
+        let path = temp_log("levels");
+        let logger = FileLogger::new(path.clone(), LevelFilter::Info, MAX_LOG_BYTES, 1);
+        log_at(&logger, Level::Info, "turn 1");
+        log_at(&logger, Level::Debug, "engine state");
+        logger.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+        assert!(contents.contains("INFO  crackle::session: turn 1"));
+    }
+
+    #[test]
+    fn test_log_rotates_and_keeps_newest() {
+        let path = temp_log("rotate");
+        let logger = FileLogger::new(path.clone(), LevelFilter::Trace, 10, 2);
+        for n in 0..4 {
+            log_at(&logger, Level::Info, &format!("line {n}"));
+        }
+
+        let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+        assert!(read(&path).contains("line 3"));
+        assert!(read(&rotated_path(&path, 1)).contains("line 2"));
+        assert!(read(&rotated_path(&path, 2)).contains("line 1"));
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_env_overrides_config_level() {
+        let config = Config::default();
+        assert_eq!(resolve_level(&config, None).unwrap(), LevelFilter::Info);
+        assert_eq!(
+            resolve_level(&config, Some("DEBUG")).unwrap(),
+            LevelFilter::Debug
+        );
+        assert_eq!(resolve_level(&config, Some("")).unwrap(), LevelFilter::Info);
+        assert!(matches!(
+            resolve_level(&config, Some("loud")),
+            Err(LogError::InvalidLevel(_))
+        ));
+    }
+}
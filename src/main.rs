@@ -1,17 +1,39 @@
+use clap::Parser;
 use crackle::{
-    config::{Config, get_config},
-    database,
-    input::{InteractiveInput, TestInput},
-    output::{InteractiveOutput, TestOutput},
-    session::Session,
+    archive::{Archive, ArchivedPuzzle},
+    benchmark,
+    cli::{BUILTIN_SOURCE, BenchmarkArgs, Cli, Command, ConfigAction, SourcesAction, Status},
+    config::{
+        Config, ConfigError, PastAnswerMode, check_past_answer_weight, config_path, get_config,
+        read_config,
+    },
+    database::{self, SessionFilter},
+    error::{FatalError, RecoverableError},
+    export,
+    input::{InteractiveInput, TestInput, TuiInput},
+    logs,
+    output::{InteractiveOutput, TestOutput, TuiOutput},
+    past_answers::parse_past_answers,
+    protocol,
+    replay::{self, ReplayError, ReplayFile},
+    report::{Leaderboard, Report},
+    server,
     session::SessionType,
-    setup::{self},
+    session::{ActiveSession, Session, SessionOutcome, SessionResults, draw_seed},
+    setup::{self, SetupError},
     shared_state::SharedTestState,
+    suggest,
+    tui::Tui,
+    word_list,
 };
-use dialoguer::{Select, theme::ColorfulTheme};
+use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use std::cell::RefCell;
+use std::error::Error;
 use std::fs;
-use std::io::BufReader;
+use std::io::{BufReader, IsTerminal, Write};
+use std::process::ExitCode;
 use std::rc::Rc;
 
 // #[derive(Default)]
@@ -28,51 +50,682 @@ use std::rc::Rc;
 // }
 
 // we need to make sure the crackle db exists in the app config directory and then create it if it doesn't, also we need to make a in memory word db to query.
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Embed the word list in the binary
-    const WORD_LIST: &str = include_str!("words.txt");
-    let config = get_config();
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+    match run(cli) {
+        Ok(status) => status.into(),
+        Err(err) => {
+            log::error!("{err}");
+            let status = error_status(err.as_ref());
+            report_error(json, status, &err.to_string());
+            status.into()
+        }
+    }
+}
 
-    let in_memory_word_db = setup::setup_word_db(WORD_LIST)?;
-    let db_exists = fs::metadata(&config.app_db).is_ok();
+/// Prints a JSON document on stdout for `--json`.
+fn print_json(value: &serde_json::Value) {
+    // A reader that stops early, such as `head`, is not an error.
+    let _ = writeln!(std::io::stdout(), "{value:#}");
+}
 
-    let result_db = if db_exists {
-        database::DB::new(&config)?
+/// Reports a failure on stderr, or as a JSON error object on stdout with `--json`.
+fn report_error(json: bool, status: Status, message: &str) {
+    if json {
+        print_json(&serde_json::json!({
+            "error": message,
+            "status": status.as_str(),
+            "exit_code": status as u8,
+        }));
     } else {
-        let db = database::DB::new(&config)?;
-        db.create_session_table()?;
-        db
+        eprintln!("Error: {message}");
+    }
+}
+
+/// Picks the exit status for an error that stopped a command.
+fn error_status(err: &(dyn Error + 'static)) -> Status {
+    if let Some(SetupError::UnknownWordSource(_)) = err.downcast_ref::<SetupError>() {
+        return Status::NotFound;
+    }
+    if let Some(SetupError::InvalidSourceName(_)) = err.downcast_ref::<SetupError>() {
+        return Status::Invalid;
+    }
+    if let Some(ConfigError::UnknownKey(_)) = err.downcast_ref::<ConfigError>() {
+        return Status::NotFound;
+    }
+    if err.is::<RecoverableError>() {
+        return Status::Invalid;
+    }
+    if let Some(replay_err) = err.downcast_ref::<ReplayError>() {
+        return match replay_err {
+            ReplayError::Io(io_err) if io_err.kind() == std::io::ErrorKind::NotFound => {
+                Status::NotFound
+            }
+            ReplayError::Json(_)
+            | ReplayError::UnsupportedVersion(_)
+            | ReplayError::InvalidFeedback { .. }
+            | ReplayError::InvalidGuess { .. } => Status::Invalid,
+            _ => Status::Failure,
+        };
+    }
+    if let Some(io_err) = err.downcast_ref::<std::io::Error>()
+        && io_err.kind() == std::io::ErrorKind::NotFound
+    {
+        return Status::NotFound;
+    }
+    Status::Failure
+}
+
+fn run(cli: Cli) -> Result<Status, Box<dyn Error>> {
+    // Suggest never writes anything, so it runs before the config file, the log file and the results database are created.
+    if let Some(Command::Suggest { history, limit }) = cli.command {
+        let mut config = read_config();
+        cli.overrides.apply(&mut config);
+        return suggest_command(history, limit, &config, cli.json);
+    }
+    let mut config = get_config();
+    cli.overrides.apply(&mut config);
+    if let Err(e) = logs::init(&config) {
+        eprintln!("Logging is disabled: {e}");
+    }
+
+    let json = cli.json;
+    let command = match cli.command {
+        None | Some(Command::Play | Command::Tui) if json => {
+            report_error(
+                json,
+                Status::Usage,
+                "Interactive games have no JSON output, use `crackle api` to drive games from another program",
+            );
+            return Ok(Status::Usage);
+        }
+        None => return menu_loop(config),
+        Some(Command::Config { action }) => return config_command(action, &config, json),
+        Some(command) => command,
     };
 
-    loop {
-        let err = menu(&in_memory_word_db, &result_db, &config);
-        match err {
-            Ok(_) => {}
-            Err(err) => {
-                let msg = err.to_string();
-                if msg.contains("Exit") {
-                    continue;
+    let result_db = database::DB::new(&config)?;
+    result_db.migrate()?;
+    if command.records_sessions() {
+        result_db.add_profile(config.profile())?;
+    }
+
+    match command {
+        Command::Play => {
+            let word_db = setup::load_active_word_db(&config, &result_db)?;
+            play_command(&config, &result_db, &word_db)
+        }
+        Command::Tui => {
+            let word_db = setup::load_active_word_db(&config, &result_db)?;
+            Ok(tui_session(&config, &result_db, &word_db)?.into())
+        }
+        Command::Test { runs } => {
+            let word_db = setup::load_active_word_db(&config, &result_db)?;
+            let runs = runs.unwrap_or(config.test_runs);
+            let seed = config
+                .seed
+                .unwrap_or_else(|| draw_seed(&mut rand::thread_rng()));
+            let results = test_session(runs, seed, &config, &result_db, &word_db)?;
+            let wins = results.iter().filter(|results| results.win).count();
+            if json {
+                let games: Vec<serde_json::Value> = results
+                    .iter()
+                    .map(|results| {
+                        serde_json::json!({
+                            "session_id": results.session_id.to_string(),
+                            "word": results.word,
+                            "outcome": results.outcome,
+                            "win": results.win,
+                            "guesses": results.number_of_guesses,
+                            "seed": results.seed,
+                        })
+                    })
+                    .collect();
+                print_json(&serde_json::json!({
+                    "seed": seed,
+                    "runs": runs,
+                    "wins": wins,
+                    "losses": results.len() - wins,
+                    "games": games,
+                }));
+            } else {
+                println!("Won {wins} of {runs} games (seed {seed})");
+            }
+            Ok(if wins == runs as usize {
+                Status::Success
+            } else {
+                Status::Lost
+            })
+        }
+        Command::Report {
+            since,
+            until,
+            session_type,
+            leaderboard,
+            output,
+        } => {
+            let filter = SessionFilter {
+                session_type,
+                profile: (!leaderboard).then(|| config.profile().to_string()),
+                ..SessionFilter::between(since, until)
+            };
+            let (colored, plain, report) = render_report(&result_db, filter, leaderboard)?;
+            if json {
+                print_json(&report);
+            } else if std::io::stdout().is_terminal() {
+                println!("{colored}");
+            } else {
+                println!("{plain}");
+            }
+            if let Some(path) = output {
+                fs::write(&path, plain)?;
+            }
+            Ok(Status::Success)
+        }
+        Command::Replay { file } => replay_command(&file, config, &result_db, json),
+        Command::Benchmark(args) => {
+            benchmark_command(&args, cli.overrides.seed, &config, &result_db, json)
+        }
+        Command::Api => {
+            let word_db = setup::load_active_word_db(&config, &result_db)?;
+            protocol::serve(
+                std::io::stdin().lock(),
+                std::io::stdout(),
+                &config,
+                &result_db,
+                &word_db,
+            )?;
+            Ok(Status::Success)
+        }
+        Command::Serve { addr } => {
+            let word_db = setup::load_active_word_db(&config, &result_db)?;
+            server::serve(&addr, &config, &result_db, &word_db)?;
+            Ok(Status::Success)
+        }
+        Command::Sources { action } => sources_command(action, &result_db, json),
+        Command::Suggest { .. } | Command::Config { .. } => unreachable!(),
+    }
+}
+
+/// Plays one game reading feedback from stdin. Exits with `Lost` unless the answer is found, or `Abandoned` if the player leaves.
+fn play_command(
+    config: &Config,
+    result_db: &database::DB,
+    word_db: &database::DB,
+) -> Result<Status, Box<dyn Error>> {
+    let input = InteractiveInput::new(BufReader::new(std::io::stdin()));
+    let output = InteractiveOutput::new(std::io::stdout());
+    let mut session = Session::new(
+        SessionType::Interactive,
+        input,
+        output,
+        config,
+        result_db,
+        word_db,
+    );
+    session.capture_replays(config.replay_dir());
+    session.initialize()?;
+    Ok(session.start()?.into())
+}
+
+/// Prints the next guess for a history given as arguments or on stdin. Exits with `Lost` when no word matches the feedback.
+fn suggest_command(
+    history: Vec<String>,
+    limit: usize,
+    config: &Config,
+    json: bool,
+) -> Result<Status, Box<dyn Error>> {
+    let history = if history.is_empty() && !std::io::stdin().is_terminal() {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        history.join(" ")
+    };
+    let history = suggest::parse_history(&history)?;
+    let result_db = database::DB::open_read_only(config)?;
+    let word_db = setup::read_active_word_db(config, result_db.as_ref())?;
+    let past_answers = match (config.past_answer_mode, &result_db) {
+        (PastAnswerMode::Ignore, _) | (_, None) => Default::default(),
+        (_, Some(result_db)) => result_db.get_past_answers()?,
+    };
+
+    let suggestion = suggest::suggest(&history, &word_db, config, past_answers)?;
+    if json {
+        print_json(&suggestion.to_json(limit));
+        return Ok(if suggestion.solved || suggestion.guess.is_some() {
+            Status::Success
+        } else {
+            Status::Lost
+        });
+    }
+    if suggestion.solved {
+        println!("Already solved");
+        return Ok(Status::Success);
+    }
+    let Some(guess) = suggestion.guess else {
+        println!("No words match this feedback");
+        return Ok(Status::Lost);
+    };
+    println!("Next guess: {guess}");
+    println!("{} candidates remaining:", suggestion.candidates.len());
+    for (word, probability) in suggestion.candidates.iter().take(limit) {
+        println!("  {word} {probability:.4}");
+    }
+    if suggestion.candidates.len() > limit {
+        println!("  ... and {} more", suggestion.candidates.len() - limit);
+    }
+    Ok(Status::Success)
+}
+
+/// Re-runs a replay file with the word source it was recorded with. Exits with `Invalid` when the engine diverges.
+fn replay_command(
+    path: &std::path::Path,
+    mut config: Config,
+    result_db: &database::DB,
+    json: bool,
+) -> Result<Status, Box<dyn Error>> {
+    let file = ReplayFile::load(path)?;
+    config.active_word_source =
+        (file.word_source != BUILTIN_SOURCE).then(|| file.word_source.clone());
+    let word_db = setup::load_active_word_db(&config, result_db)?;
+    let word_source_hash = setup::active_word_source_hash(&config, result_db)?;
+    let report = replay::replay(&file, &word_db, &word_source_hash)?;
+    let status = if report.first_divergence().is_some() {
+        Status::Invalid
+    } else {
+        Status::Success
+    };
+
+    if json {
+        let mut value = serde_json::to_value(&report)?;
+        value["session_id"] = serde_json::json!(file.session_id);
+        value["diverged"] = serde_json::json!(status == Status::Invalid);
+        print_json(&value);
+        return Ok(status);
+    }
+    println!(
+        "Replaying {} ({}), recorded by crackle {}",
+        file.session_id, file.reason, file.crackle_version
+    );
+    for warning in &report.warnings {
+        println!("Warning: {warning}");
+    }
+    for step in &report.steps {
+        println!(
+            "Turn {}: {} {}  known {}  {} candidates",
+            step.turn, step.guess, step.feedback, step.pattern, step.candidates
+        );
+        for divergence in &step.divergences {
+            println!("  ! {divergence}");
+        }
+    }
+    match (&report.next_guess, report.stumped) {
+        (Some(guess), _) => println!("Next guess: {guess}"),
+        (None, true) => println!("The engine is stumped"),
+        (None, false) => println!("Solved"),
+    }
+    match report.first_divergence() {
+        Some(step) => println!("First divergence on turn {}", step.turn),
+        None => println!("Replay matches the recording"),
+    }
+    Ok(status)
+}
+
+/// Plays every target word, stores the run and prints its summary. Subset lines that cannot be used are skipped.
+/// With `--compare` the run is checked against a saved baseline and `Regressed` is returned if it passes a threshold.
+fn benchmark_command(
+    args: &BenchmarkArgs,
+    seed_flag: Option<u64>,
+    config: &Config,
+    result_db: &database::DB,
+    json: bool,
+) -> Result<Status, Box<dyn Error>> {
+    let baseline = match &args.compare {
+        Some(name) => match result_db.get_benchmark_baseline(name)? {
+            Some(baseline) => Some((name, baseline)),
+            None => {
+                report_error(
+                    json,
+                    Status::NotFound,
+                    &format!("No benchmark baseline named '{name}'"),
+                );
+                return Ok(Status::NotFound);
+            }
+        },
+        None => None,
+    };
+    let subset = args.words.as_deref();
+    let word_db = setup::load_active_word_db(config, result_db)?;
+    let mut skipped = Vec::new();
+    let targets = match subset {
+        None => word_db.get_words()?,
+        Some(path) => {
+            let report = word_list::validate_word_list(&fs::read_to_string(path)?);
+            skipped.extend(report.issues.iter().map(ToString::to_string));
+            let mut targets = Vec::new();
+            for word in report.words {
+                if word_db.contains_word(&word)? {
+                    targets.push(word);
                 } else {
-                    println!("Error in menu: {err}");
-                    break;
+                    skipped.push(format!("'{word}', it is not in the active word list"));
                 }
             }
+            targets
+        }
+    };
+    if targets.is_empty() {
+        report_error(json, Status::Invalid, "No words to benchmark");
+        return Ok(Status::Invalid);
+    }
+
+    let show_progress = std::io::stderr().is_terminal();
+    // A comparison replays the baseline's opener unless `--seed` asks for another one on this run.
+    // A seed from config.toml does not count, as it was not picked for this comparison.
+    let seed = seed_flag
+        .or(baseline.as_ref().map(|(_, baseline)| baseline.seed))
+        .or(config.seed)
+        .unwrap_or_else(|| draw_seed(&mut rand::thread_rng()));
+    let mut run = benchmark::run(
+        targets,
+        subset.map(|path| path.display().to_string()),
+        seed,
+        config,
+        result_db,
+        &word_db,
+        |done, total| {
+            if show_progress && (done % 100 == 0 || done == total) {
+                eprint!("\rBenchmarking: {done}/{total} words");
+                if done == total {
+                    eprintln!();
+                }
+            }
+        },
+    )?;
+    let id = result_db.store_benchmark_run(&run)?;
+    run.id = Some(id);
+    let comparison = baseline
+        .map(|(name, baseline)| benchmark::compare(name, &baseline, &run, args.thresholds()));
+    if let Some(name) = &args.save_baseline {
+        result_db.save_benchmark_baseline(name, id)?;
+    }
+    let status = match &comparison {
+        Some(comparison) if comparison.regressed() => Status::Regressed,
+        _ => Status::Success,
+    };
+
+    if json {
+        let mut value = run.to_json(args.worst);
+        value["skipped"] = serde_json::json!(skipped);
+        value["comparison"] = comparison.map_or(serde_json::Value::Null, |c| c.to_json());
+        value["baseline_saved"] = serde_json::json!(args.save_baseline);
+        print_json(&value);
+        return Ok(status);
+    }
+    for skip in &skipped {
+        println!("Skipped {skip}");
+    }
+    print!("{}", benchmark::render(&run, args.worst));
+    println!("Stored as benchmark run #{id}");
+    if let Some(comparison) = &comparison {
+        print!("{}", benchmark::render_comparison(comparison));
+    }
+    if let Some(name) = &args.save_baseline {
+        println!("Saved as baseline '{name}'");
+    }
+    Ok(status)
+}
+
+fn sources_command(
+    action: SourcesAction,
+    result_db: &database::DB,
+    json: bool,
+) -> Result<Status, Box<dyn Error>> {
+    let sources = result_db.get_word_sources()?;
+    let is_registered = |name: &str| sources.iter().any(|source| source.name == name);
+    let not_registered = |name: &str| {
+        report_error(
+            json,
+            Status::NotFound,
+            &format!("Word source '{name}' is not registered"),
+        );
+        Ok(Status::NotFound)
+    };
+    // Changes are saved to the config file as it is on disk, without any command line overrides.
+    let mut saved = get_config();
+
+    match action {
+        SourcesAction::List => {
+            let active = saved.active_word_source.as_deref();
+            if json {
+                let sources: Vec<serde_json::Value> = sources
+                    .iter()
+                    .map(|source| {
+                        serde_json::json!({
+                            "name": source.name,
+                            "path": source.path,
+                            "word_count": source.word_count,
+                            "added_date": source.added_date.to_rfc3339(),
+                        })
+                    })
+                    .collect();
+                print_json(&serde_json::json!({
+                    "active": active.unwrap_or(BUILTIN_SOURCE),
+                    "sources": sources,
+                }));
+                return Ok(Status::Success);
+            }
+            let marker = |selected: bool| if selected { " (active)" } else { "" };
+            println!("{BUILTIN_SOURCE}{}", marker(active.is_none()));
+            for source in &sources {
+                println!(
+                    "{} - {} words from {}{}",
+                    source.name,
+                    source.word_count,
+                    source.path,
+                    marker(active == Some(source.name.as_str()))
+                );
+            }
+        }
+        SourcesAction::Add { name, path } => {
+            let count = setup::register_word_source(result_db, &name, &path)?;
+            if json {
+                print_json(&serde_json::json!({ "name": name, "word_count": count }));
+            } else {
+                println!("Registered '{name}' with {count} words");
+            }
+        }
+        SourcesAction::Use { name } => {
+            if name != BUILTIN_SOURCE && !is_registered(&name) {
+                return not_registered(&name);
+            }
+            saved.active_word_source = (name != BUILTIN_SOURCE).then_some(name);
+            saved.save()?;
+            let active = saved
+                .active_word_source
+                .as_deref()
+                .unwrap_or(BUILTIN_SOURCE);
+            if json {
+                print_json(&serde_json::json!({ "active": active }));
+            } else {
+                println!("Now solving with {active}");
+            }
+        }
+        SourcesAction::Remove { name } => {
+            if !is_registered(&name) {
+                return not_registered(&name);
+            }
+            result_db.delete_word_source(&name)?;
+            if saved.active_word_source.as_deref() == Some(name.as_str()) {
+                saved.active_word_source = None;
+                saved.save()?;
+            }
+            if json {
+                print_json(&serde_json::json!({ "removed": name }));
+            } else {
+                println!("Removed '{name}'");
+            }
+        }
+        SourcesAction::Validate {
+            path,
+            write_cleaned,
+        } => {
+            let report = setup::validate_word_source(&path)?;
+            if json {
+                let issues: Vec<serde_json::Value> = report
+                    .issues
+                    .iter()
+                    .map(|issue| {
+                        serde_json::json!({
+                            "line": issue.line,
+                            "entry": issue.entry,
+                            "message": issue.to_string(),
+                        })
+                    })
+                    .collect();
+                print_json(&serde_json::json!({
+                    "valid_words": report.words.len(),
+                    "skipped_lines": report.skipped_lines,
+                    "issues": issues,
+                }));
+            } else {
+                print!("{report}");
+            }
+            if let Some(cleaned_path) = write_cleaned {
+                fs::write(&cleaned_path, report.cleaned())?;
+            }
+            if !report.is_clean() {
+                return Ok(Status::Invalid);
+            }
+        }
+    }
+    Ok(Status::Success)
+}
+
+fn config_command(
+    action: ConfigAction,
+    config: &Config,
+    json: bool,
+) -> Result<Status, Box<dyn Error>> {
+    match action {
+        ConfigAction::Show if json => print_json(&serde_json::to_value(config)?),
+        ConfigAction::Show => print!("{}", toml::to_string(config)?),
+        ConfigAction::Path if json => {
+            print_json(&serde_json::json!({ "path": config_path() }));
+        }
+        ConfigAction::Path => println!("{}", config_path().display()),
+        ConfigAction::Set { key, value } => {
+            let mut saved = get_config();
+            saved.set_value(&key, Some(&value))?;
+            saved.save()?;
+            if json {
+                print_json(&serde_json::json!({ "key": key, "value": value }));
+            }
+        }
+        ConfigAction::Unset { key } => {
+            let mut saved = get_config();
+            saved.set_value(&key, None)?;
+            saved.save()?;
+            if json {
+                print_json(&serde_json::json!({ "key": key, "value": null }));
+            }
+        }
+    }
+    Ok(Status::Success)
+}
+
+/// Runs the interactive menu until the user quits.
+fn menu_loop(mut config: Config) -> Result<Status, Box<dyn Error>> {
+    let result_db = database::DB::new(&config)?;
+    result_db.migrate()?;
+    select_profile(&mut config, &result_db)?;
+
+    let mut in_memory_word_db = match setup::load_active_word_db(&config, &result_db) {
+        Err(SetupError::UnknownWordSource(name)) => {
+            eprintln!("Word source '{name}' is not registered, using the built-in list instead");
+            config.active_word_source = None;
+            setup::load_active_word_db(&config, &result_db)?
+        }
+        word_db => word_db?,
+    };
+
+    loop {
+        if let Err(err) = menu(&mut in_memory_word_db, &result_db, &mut config) {
+            println!("Error in menu: {err}");
+            return Ok(Status::Failure);
         }
     }
+}
 
+/// Asks who is playing and saves the choice as the active profile. New profiles can be created from the picker.
+fn select_profile(
+    config: &mut Config,
+    result_db: &database::DB,
+) -> Result<(), Box<dyn std::error::Error>> {
+    result_db.add_profile(config.profile())?;
+    let profiles = result_db.get_profiles()?;
+
+    let mut items = profiles.clone();
+    items.push("Create a new profile".to_string());
+    let active = profiles
+        .iter()
+        .position(|name| name == config.profile())
+        .unwrap_or(0);
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Who is playing?")
+        .items(&items)
+        .default(active)
+        .interact()?;
+
+    let profile = match profiles.get(selection) {
+        Some(name) => name.clone(),
+        None => {
+            let name: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Profile name")
+                .validate_with(|name: &String| {
+                    if name.trim().is_empty() {
+                        Err("Profile name cannot be empty")
+                    } else {
+                        Ok(())
+                    }
+                })
+                .interact_text()?;
+            let name = name.trim().to_string();
+            result_db.add_profile(&name)?;
+            name
+        }
+    };
+
+    if profile != config.profile() {
+        save_setting(config, |config| {
+            config.active_profile = Some(profile.clone())
+        })?;
+    }
     Ok(())
 }
 
+/// Changes a setting in the running config and in `config.toml` as it is on disk, so command line overrides are not saved.
+fn save_setting(config: &mut Config, change: impl Fn(&mut Config)) -> Result<(), std::io::Error> {
+    change(config);
+    let mut saved = get_config();
+    change(&mut saved);
+    saved.save()
+}
+
 fn menu(
-    in_memory_db: &database::DB,
+    in_memory_db: &mut database::DB,
     result_db: &database::DB,
-    config: &Config,
+    config: &mut Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let selections = &[
         "Interactive Session",
+        "Full-screen Session",
         "Test Session",
+        "Solve Archived Puzzles",
         "Generate Report",
         "Change Word Source",
+        "Manage Past Answers",
+        "Export Session History",
+        "Import Session History",
         "Quit",
     ];
     let selection = Select::with_theme(&ColorfulTheme::default())
@@ -82,9 +735,24 @@ fn menu(
         .unwrap();
     match selection {
         0 => interactive_session(config, result_db, in_memory_db)?,
-        1 => test_session(config, result_db, in_memory_db)?,
-        // 2 => change_word_src(game)?,
-        4 => std::process::exit(0),
+        1 => {
+            tui_session(config, result_db, in_memory_db)?;
+        }
+        2 => {
+            let seed = config
+                .seed
+                .unwrap_or_else(|| draw_seed(&mut rand::thread_rng()));
+            let results = test_session(config.test_runs, seed, config, result_db, in_memory_db)?;
+            let wins = results.iter().filter(|results| results.win).count();
+            println!("Won {wins} of {} games (seed {seed})", results.len());
+        }
+        3 => archive_session(config, result_db, in_memory_db)?,
+        4 => generate_report(config, result_db)?,
+        5 => change_word_src(config, result_db, in_memory_db)?,
+        6 => manage_past_answers(config, result_db)?,
+        7 => export_history(result_db)?,
+        8 => import_history(result_db)?,
+        9 => std::process::exit(0),
         _ => unreachable!(),
     }
     Ok(())
@@ -106,44 +774,486 @@ fn interactive_session(
         result_db,
         in_memory_db,
     );
-    session.initialize()?;
-    session.start_interactive()?;
+    session.capture_replays(config.replay_dir());
+    match select_session_to_resume(config, result_db)? {
+        Some(active) => {
+            let history = result_db.get_guesses(&active.session_id)?;
+            session.resume(&active, &history)?;
+        }
+        None => session.initialize()?,
+    }
+    session.start()?;
     Ok(())
 }
 
+/// Plays one game on the full-screen terminal UI and returns how it ended.
+/// It is stored as an interactive session, so an unfinished game can be resumed from the line-based session.
+fn tui_session(
+    config: &Config,
+    result_db: &database::DB,
+    in_memory_db: &database::DB,
+) -> Result<SessionOutcome, Box<dyn std::error::Error>> {
+    let tui = Rc::new(RefCell::new(Tui::new(config.max_guesses)?));
+    let mut session = Session::new(
+        SessionType::Interactive,
+        TuiInput::new(Rc::clone(&tui)),
+        TuiOutput::new(Rc::clone(&tui)),
+        config,
+        result_db,
+        in_memory_db,
+    );
+    session.capture_replays(config.replay_dir());
+    session.initialize()?;
+    let outcome = session.start()?;
+
+    let results = session.get_session_results();
+    let message = if outcome == SessionOutcome::Won {
+        format!(
+            "Solved '{}' in {} guesses!",
+            results.word, results.number_of_guesses
+        )
+    } else if outcome == SessionOutcome::Abandoned {
+        "Game stored as abandoned. Resume it from the interactive session.".to_string()
+    } else if session.is_answer_known() {
+        format!("The answer was '{}'. Game stored.", results.word)
+    } else {
+        "Game stored.".to_string()
+    };
+    tui.borrow_mut().finish(&message)?;
+    Ok(outcome)
+}
+
+/// Offers to resume an unfinished interactive session from today, returning the one picked or `None` for a new game.
+fn select_session_to_resume(
+    config: &Config,
+    result_db: &database::DB,
+) -> Result<Option<ActiveSession>, Box<dyn std::error::Error>> {
+    let start_of_today = chrono::Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+        .map(|midnight| midnight.with_timezone(&chrono::Utc))
+        .unwrap_or_else(chrono::Utc::now);
+    let mut unfinished = result_db.get_active_sessions(start_of_today, config.profile())?;
+    unfinished.retain(|active| active.session_type == SessionType::Interactive.as_str());
+    if unfinished.is_empty() {
+        return Ok(None);
+    }
+
+    let mut items: Vec<String> = unfinished
+        .iter()
+        .map(|active| {
+            format!(
+                "Resume game started at {} (next guess: {})",
+                active
+                    .start_date
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M"),
+                active.current_guess
+            )
+        })
+        .collect();
+    items.push("Start a new game".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("You have an unfinished game from today")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(unfinished.into_iter().nth(selection))
+}
+
+/// Plays `runs` games against random words and returns their results in the order they were played.
+/// The targets and each game's own seed are drawn from `seed`, so the same seed and word list replay the same run.
 fn test_session(
+    runs: u8,
+    seed: u64,
+    config: &Config,
+    result_db: &database::DB,
+    in_memory_db: &database::DB,
+) -> Result<Vec<SessionResults>, Box<dyn std::error::Error>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut results = Vec::new();
+    for _ in 0..runs {
+        let random_word = in_memory_db.get_random_word(&mut rng)?;
+        results.push(run_test_game(
+            SessionType::Test,
+            random_word,
+            draw_seed(&mut rng),
+            config,
+            result_db,
+            in_memory_db,
+        )?);
+    }
+
+    Ok(results)
+}
+
+/// Plays one game against a known answer with `TestInput` and returns the results it was stored with.
+fn run_test_game(
+    session_type: SessionType,
+    answer: String,
+    seed: u64,
+    config: &Config,
+    result_db: &database::DB,
+    in_memory_db: &database::DB,
+) -> Result<SessionResults, FatalError> {
+    let shared_state = Rc::new(RefCell::new(SharedTestState::new()));
+    let input = TestInput::new(answer, Rc::clone(&shared_state));
+    let output = TestOutput::new(Rc::clone(&shared_state));
+    let mut session = Session::new(session_type, input, output, config, result_db, in_memory_db);
+    session.set_seed(seed);
+    session.capture_replays(config.replay_dir());
+    session.initialize()?;
+    session.start()?;
+    Ok(session.get_session_results())
+}
+
+fn archive_session(
     config: &Config,
     result_db: &database::DB,
     in_memory_db: &database::DB,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let runs = config.test_runs;
+    let archive_path = config.answer_archive_path();
+    let (archive, issues) = Archive::parse(&fs::read_to_string(&archive_path).map_err(|e| {
+        format!(
+            "Could not read answer archive {}: {e}",
+            archive_path.display()
+        )
+    })?);
+    for issue in &issues {
+        println!("Skipped {issue}");
+    }
 
-    for _ in 0..runs {
-        let random_word = in_memory_db.get_random_word()?;
+    let modes = &["Replay puzzle #N", "Replay a date range"];
+    let mode = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which puzzles?")
+        .items(modes)
+        .default(0)
+        .interact()?;
+    let puzzles: Vec<&ArchivedPuzzle> = if mode == 0 {
+        let number: u32 = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Puzzle number")
+            .interact_text()?;
+        archive.find_number(number).into_iter().collect()
+    } else {
+        let from: chrono::NaiveDate = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("From date (YYYY-MM-DD)")
+            .interact_text()?;
+        let to: chrono::NaiveDate = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("To date (YYYY-MM-DD)")
+            .interact_text()?;
+        archive.in_date_range(from, to)
+    };
+    let (Some(first), Some(last)) = (puzzles.first(), puzzles.last()) else {
+        println!("No archived puzzles match");
+        return Ok(());
+    };
+    let (from, to) = (first.number, last.number);
+    let numbers: Vec<u32> = puzzles.iter().map(|puzzle| puzzle.number).collect();
 
-        let shared_state = Rc::new(RefCell::new(SharedTestState::new()));
-        let input = TestInput::new(random_word, Rc::clone(&shared_state));
-        let output = TestOutput::new(Rc::clone(&shared_state));
-        let mut session = Session::new(
-            SessionType::Test,
-            input,
-            output,
+    let mut rng = StdRng::seed_from_u64(
+        config
+            .seed
+            .unwrap_or_else(|| draw_seed(&mut rand::thread_rng())),
+    );
+    for puzzle in &puzzles {
+        let results = run_test_game(
+            SessionType::Archive,
+            puzzle.answer.clone(),
+            draw_seed(&mut rng),
             config,
             result_db,
             in_memory_db,
-        );
-        session.initialize()?;
-        session.start_test_session()?;
+        )?;
+        result_db.store_archive_run(
+            &results.session_id,
+            puzzle.number,
+            &puzzle.answer,
+            env!("CARGO_PKG_VERSION"),
+        )?;
     }
 
+    println!(
+        "Archive runs for the {} selected puzzles, #{from} to #{to}:",
+        numbers.len()
+    );
+    for summary in result_db.get_archive_summaries(&numbers)? {
+        println!(
+            "  v{}: {} runs, {} wins, average guesses {}",
+            summary.crackle_version,
+            summary.runs,
+            summary.wins,
+            summary
+                .average_guesses
+                .map_or("-".to_string(), |avg| format!("{avg:.2}"))
+        );
+    }
     Ok(())
 }
 
-#[allow(dead_code, unused_variables)]
-fn api_session(
+fn generate_report(
     config: &Config,
     result_db: &database::DB,
-    in_memory_db: &database::DB,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let views = &[
+        format!("My statistics ({})", config.profile()),
+        "Leaderboard (all profiles)".to_string(),
+    ];
+    let view = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which report?")
+        .items(views)
+        .default(0)
+        .interact()?;
+    let leaderboard = view == 1;
+    let filter = SessionFilter {
+        since: select_since()?,
+        profile: (!leaderboard).then(|| config.profile().to_string()),
+        ..Default::default()
+    };
+    let (colored, plain, _) = render_report(result_db, filter, leaderboard)?;
+    println!("{colored}");
+
+    let save = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Save a plain text copy of this report?")
+        .default(false)
+        .interact()?;
+    if save {
+        let path = config.app_db.with_file_name("report.txt");
+        fs::write(&path, plain)?;
+        println!("Report saved to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Renders the active profile's report, or the leaderboard across profiles, as coloured text, plain text and JSON.
+fn render_report(
+    result_db: &database::DB,
+    filter: SessionFilter,
+    leaderboard: bool,
+) -> Result<(String, String, serde_json::Value), rusqlite::Error> {
+    let sessions = result_db.get_session_results(&filter)?;
+    Ok(if leaderboard {
+        let leaderboard = Leaderboard::new(filter, &sessions);
+        (
+            leaderboard.render_colored(),
+            leaderboard.render_plain(),
+            leaderboard.to_json(),
+        )
+    } else {
+        let report = Report::new(filter, &sessions);
+        (
+            report.render_colored(),
+            report.render_plain(),
+            report.to_json(),
+        )
+    })
+}
+
+fn change_word_src(
+    config: &mut Config,
+    result_db: &database::DB,
+    in_memory_db: &mut database::DB,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sources = result_db.get_word_sources()?;
+    let active = config.active_word_source.as_deref();
+    let marker = |selected: bool| if selected { " (active)" } else { "" };
+
+    let mut items = vec![format!("Built-in list{}", marker(active.is_none()))];
+    items.extend(sources.iter().map(|source| {
+        format!(
+            "{} - {} words from {}{}",
+            source.name,
+            source.word_count,
+            source.path,
+            marker(active == Some(source.name.as_str()))
+        )
+    }));
+    items.push("Register a new word list...".to_string());
+    items.push("Validate a word list file...".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which word source should the solver use?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    if selection == items.len() - 1 {
+        return validate_word_src();
+    }
+
+    let name = if selection == 0 {
+        None
+    } else if selection <= sources.len() {
+        Some(sources[selection - 1].name.clone())
+    } else {
+        let name: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Name for the new word source")
+            .interact_text()?;
+        let path = setup::get_working_directory()?.join(setup::get_new_word_source_path()?);
+        let count = setup::register_word_source(result_db, name.trim(), &path)?;
+        println!("Registered '{}' with {count} words", name.trim());
+        Some(name.trim().to_string())
+    };
+
+    let mut updated = config.clone();
+    updated.active_word_source = name.clone();
+    *in_memory_db = setup::load_active_word_db(&updated, result_db)?;
+    save_setting(config, |config| config.active_word_source = name.clone())?;
+    println!(
+        "Now solving with {}",
+        config
+            .active_word_source
+            .as_deref()
+            .unwrap_or("the built-in list")
+    );
+    Ok(())
+}
+
+fn validate_word_src() -> Result<(), Box<dyn std::error::Error>> {
+    let path = setup::get_working_directory()?.join(setup::get_new_word_source_path()?);
+    let report = setup::validate_word_source(&path)?;
+    print!("{report}");
+
+    let write_cleaned = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Write a cleaned copy of this word list?")
+        .default(!report.is_clean())
+        .interact()?;
+    if write_cleaned {
+        let cleaned_path: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Write cleaned list to")
+            .default(path.with_extension("clean.txt").display().to_string())
+            .interact_text()?;
+        fs::write(cleaned_path.trim(), report.cleaned())?;
+        println!(
+            "Wrote {} words to {}",
+            report.words.len(),
+            cleaned_path.trim()
+        );
+    }
+    Ok(())
+}
+
+fn manage_past_answers(
+    config: &mut Config,
+    result_db: &database::DB,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stored = result_db.get_past_answers()?.len();
+    let actions = &[
+        "Import past answers from a file",
+        "Add answers from stored interactive sessions",
+        "Change how past answers are treated",
+        "Clear past answers",
+    ];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "{stored} past answers stored, currently {:?}",
+            config.past_answer_mode
+        ))
+        .items(actions)
+        .default(0)
+        .interact()?;
+
+    match selection {
+        0 => {
+            let path: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt(
+                    "Past answers file (one word per line, optionally after a YYYY-MM-DD date)",
+                )
+                .interact_text()?;
+            let (answers, issues) = parse_past_answers(&fs::read_to_string(path.trim())?);
+            for issue in &issues {
+                println!("Skipped {issue}");
+            }
+            let added = result_db.store_past_answers(&answers, "file")?;
+            println!("Added {added} new past answers");
+        }
+        1 => {
+            let answers = result_db.get_known_session_answers()?;
+            let added = result_db.store_past_answers(&answers, "sessions")?;
+            println!("Added {added} new past answers from stored sessions");
+        }
+        2 => {
+            let modes = [
+                PastAnswerMode::Ignore,
+                PastAnswerMode::Drop,
+                PastAnswerMode::DownWeight,
+            ];
+            let labels = &[
+                "Ignore: rank past answers like any other word",
+                "Drop: only suggest past answers when nothing else is left",
+                "Down-weight: lower the probability of past answers",
+            ];
+            let mode = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("How should past answers be treated?")
+                .items(labels)
+                .default(0)
+                .interact()?;
+            let mode = modes[mode];
+            let weight = if mode == PastAnswerMode::DownWeight {
+                Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Weight to multiply past answer probabilities by (0.0 - 1.0)")
+                    .default(config.past_answer_weight)
+                    .validate_with(check_past_answer_weight)
+                    .interact_text()?
+            } else {
+                config.past_answer_weight
+            };
+            save_setting(config, |config| {
+                config.past_answer_mode = mode;
+                config.past_answer_weight = weight;
+            })?;
+        }
+        3 => {
+            result_db.clear_past_answers()?;
+            println!("Cleared past answers");
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn export_history(result_db: &database::DB) -> Result<(), Box<dyn std::error::Error>> {
+    let since = select_since()?;
+    let session_types = &["All", "Interactive", "Test", "Api"];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which session type?")
+        .items(session_types)
+        .default(0)
+        .interact()?;
+    let filter = SessionFilter {
+        since,
+        session_type: (selection > 0).then(|| session_types[selection].to_string()),
+        ..Default::default()
+    };
+
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Export to file (.csv or .json)")
+        .interact_text()?;
+    let count = export::export_sessions(result_db, &filter, path.trim().as_ref())?;
+    println!("Exported {count} sessions to {}", path.trim());
+    Ok(())
+}
+
+fn import_history(result_db: &database::DB) -> Result<(), Box<dyn std::error::Error>> {
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Import from file (.csv or .json)")
+        .interact_text()?;
+    let summary = export::import_sessions(result_db, path.trim().as_ref())?;
+    println!(
+        "Imported {} sessions, skipped {} already stored",
+        summary.imported, summary.skipped
+    );
     Ok(())
 }
+
+/// Asks for a date range and returns the start of it, or `None` for all time.
+fn select_since() -> Result<Option<chrono::DateTime<chrono::Utc>>, Box<dyn std::error::Error>> {
+    let ranges = &["All time", "Last 7 days", "Last 30 days", "Last 365 days"];
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Which date range?")
+        .items(ranges)
+        .default(0)
+        .interact()?;
+    let days = [None, Some(7), Some(30), Some(365)][selection];
+    Ok(days.map(|d| chrono::Utc::now() - chrono::Duration::days(d)))
+}
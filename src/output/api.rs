@@ -1,22 +1,83 @@
 use std::io::Write;
 
 use crate::output::OutputSink;
+use crate::protocol::{ServerMessage, write_message};
+use crate::session::SessionResults;
 
-#[allow(dead_code)]
+/// Writes session output as JSON-lines protocol messages.
 pub struct ApiOutput<W: Write> {
     writer: W,
 }
-#[allow(unused_variables)]
+
+impl<W: Write> ApiOutput<W> {
+    pub fn new(writer: W) -> Self {
+        ApiOutput { writer }
+    }
+}
+
 impl<W: Write> OutputSink for ApiOutput<W> {
+    /// Clients start games themselves, so the game start needs no message.
+    fn game_start(&mut self) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
     fn output_guess(&mut self, guess: &str) -> Result<(), std::io::Error> {
-        todo!("output_guess")
+        write_message(
+            &mut self.writer,
+            &ServerMessage::Guess {
+                guess: guess.to_string(),
+            },
+        )
     }
 
     fn fatal_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
-        todo!("fatal_error")
+        write_message(
+            &mut self.writer,
+            &ServerMessage::Error {
+                message: msg.to_string(),
+                fatal: true,
+            },
+        )
+    }
+
+    fn recoverable_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
+        write_message(
+            &mut self.writer,
+            &ServerMessage::Error {
+                message: msg.to_string(),
+                fatal: false,
+            },
+        )
+    }
+
+    /// A win is reported by the `result` message sent once the game is stored.
+    fn won(&mut self, _guesses: u8) -> Result<(), std::io::Error> {
+        Ok(())
     }
 
     fn out_of_guesses(&mut self) -> Result<(), std::io::Error> {
-        todo!("out_of_guesses")
+        write_message(&mut self.writer, &ServerMessage::OutOfGuesses)
+    }
+
+    fn stumped(&mut self, reason: &str) -> Result<(), std::io::Error> {
+        write_message(
+            &mut self.writer,
+            &ServerMessage::Stumped {
+                message: reason.to_string(),
+            },
+        )
+    }
+
+    fn stored(&mut self, results: &SessionResults) -> Result<(), std::io::Error> {
+        write_message(
+            &mut self.writer,
+            &ServerMessage::Result {
+                session_id: results.session_id.to_string(),
+                outcome: results.outcome,
+                win: results.win,
+                guesses: results.number_of_guesses,
+                word: results.word.clone(),
+            },
+        )
     }
 }
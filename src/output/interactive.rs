@@ -1,4 +1,7 @@
+use crate::constants::EXPECTED_FORMAT;
 use crate::output::OutputSink;
+use crate::session::{SessionOutcome, SessionResults};
+use colored::Colorize;
 use std::io::Write;
 
 pub struct InteractiveOutput<W: Write> {
@@ -16,20 +19,63 @@ impl<W: Write> InteractiveOutput<W> {
 }
 
 impl<W: Write> OutputSink for InteractiveOutput<W> {
+    fn game_start(&mut self) -> Result<(), std::io::Error> {
+        writeln!(self.writer, "Welcome to Crackle!\r\n")?;
+        writeln!(
+            self.writer,
+            "I will give you a word to try based on positional frequency"
+        )?;
+        writeln!(
+            self.writer,
+            "To achieve this, you will need to enter {}, {}, and {}",
+            "G for green".green(),
+            "Y for yellow".yellow(),
+            "N for gray".bright_black()
+        )?;
+        writeln!(self.writer, "Example: {EXPECTED_FORMAT}")?;
+        Ok(())
+    }
     fn fatal_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
         let msg = format!("Fatal error: {msg}");
         writeln!(self.writer, "{msg}")?;
         Ok(())
     }
+    fn recoverable_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
+        writeln!(self.writer, "{msg}. Please try again.")?;
+        Ok(())
+    }
     fn output_guess(&mut self, guess: &str) -> Result<(), std::io::Error> {
         writeln!(self.writer, "Guess: {guess}\r\n")?;
         Ok(())
     }
 
+    fn won(&mut self, guesses: u8) -> Result<(), std::io::Error> {
+        writeln!(self.writer, "Solved in {guesses} guesses!")?;
+        Ok(())
+    }
+
     fn out_of_guesses(&mut self) -> Result<(), std::io::Error> {
         writeln!(self.writer, "Out of guesses!")?;
         Ok(())
     }
+
+    fn stumped(&mut self, reason: &str) -> Result<(), std::io::Error> {
+        writeln!(self.writer, "I am stumped! {reason}")?;
+        Ok(())
+    }
+
+    fn stored(&mut self, results: &SessionResults) -> Result<(), std::io::Error> {
+        if results.outcome == SessionOutcome::Abandoned {
+            writeln!(
+                self.writer,
+                "Game stored as abandoned. You can resume it later today."
+            )?;
+            return Ok(());
+        }
+        writeln!(self.writer, "Game results stored successfully!")?;
+        writeln!(self.writer, "See you tomorrow!")?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -45,6 +91,15 @@ mod tests {
         assert!(msg.contains("Fatal error: Houstin we have a problem"));
     }
 
+    #[test]
+    fn test_recoverable_error_msg() {
+        let buffer = Vec::new();
+        let mut output = InteractiveOutput::new(buffer);
+        output.recoverable_error("Wrong answer").unwrap();
+        let msg = String::from_utf8(output.into_inner()).unwrap();
+        assert!(msg.contains("Wrong answer. Please try again."));
+    }
+
     #[test]
     fn test_output_guess_msg() {
         let buffer = Vec::new();
@@ -54,6 +109,25 @@ mod tests {
         assert!(msg.contains("Guess: apple"));
     }
 
+    #[test]
+    fn test_game_start_explains_feedback() {
+        let mut output = InteractiveOutput::new(Vec::new());
+        output.game_start().unwrap();
+        let msg = String::from_utf8(output.into_inner()).unwrap();
+        assert!(msg.contains("Welcome to Crackle!"));
+        assert!(msg.contains("Example: "));
+    }
+
+    #[test]
+    fn test_game_end_msgs() {
+        let mut output = InteractiveOutput::new(Vec::new());
+        output.won(3).unwrap();
+        output.stumped("No guess found").unwrap();
+        let msg = String::from_utf8(output.into_inner()).unwrap();
+        assert!(msg.contains("Solved in 3 guesses!"));
+        assert!(msg.contains("I am stumped! No guess found"));
+    }
+
     #[test]
     fn test_out_of_guesses_msg() {
         let buffer = Vec::new();
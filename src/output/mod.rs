@@ -1,13 +1,35 @@
 mod api;
 mod interactive;
 mod test;
+mod tui;
 
 pub use api::ApiOutput;
 pub use interactive::InteractiveOutput;
 pub use test::TestOutput;
+pub use tui::TuiOutput;
 
+use crate::session::SessionResults;
+use crate::word_analyzer::Word;
+
+/// Everything a `Session` has to say, in the order it happens. The session never prints on its own.
 pub trait OutputSink {
+    /// Sent once before the first guess of a new or resumed game.
+    fn game_start(&mut self) -> Result<(), std::io::Error>;
     fn output_guess(&mut self, guess: &str) -> Result<(), std::io::Error>;
-    fn fatal_error(&mut self, msg: &str) -> Result<(), std::io::Error>;
+    /// Words still consistent with the feedback, most probable first, sent before each new guess.
+    fn candidates(&mut self, _candidates: &[Word]) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+    /// The answer was found after `guesses` guesses.
+    fn won(&mut self, guesses: u8) -> Result<(), std::io::Error>;
+    /// The game was lost. The session asks the input source for the answer next.
     fn out_of_guesses(&mut self) -> Result<(), std::io::Error>;
+    /// No word in the list fits the feedback. The session asks the input source for the answer next.
+    fn stumped(&mut self, reason: &str) -> Result<(), std::io::Error>;
+    /// The finished game was written to the results database.
+    fn stored(&mut self, results: &SessionResults) -> Result<(), std::io::Error>;
+    /// An error that ended the session.
+    fn fatal_error(&mut self, msg: &str) -> Result<(), std::io::Error>;
+    /// Reports a problem the user can correct, such as an answer that does not fit the feedback.
+    fn recoverable_error(&mut self, msg: &str) -> Result<(), std::io::Error>;
 }
@@ -1,25 +1,21 @@
-use std::{cell::RefCell, io::Write, rc::Rc};
+use std::{cell::RefCell, rc::Rc};
 
-use crate::{output::OutputSink, shared_state::SharedTestState};
-#[allow(dead_code)]
+use crate::{output::OutputSink, session::SessionResults, shared_state::SharedTestState};
+
+/// Records guesses in the shared test state. Everything else is dropped, so test runs stay silent.
 pub struct TestOutput {
-    writer: Option<Box<dyn Write>>,
     shared_state: Rc<RefCell<SharedTestState>>,
 }
 
 impl TestOutput {
     pub fn new(shared_state: Rc<RefCell<SharedTestState>>) -> Self {
-        TestOutput {
-            writer: None,
-            shared_state,
-        }
+        TestOutput { shared_state }
     }
 }
 
-#[allow(unused_variables)]
 impl OutputSink for TestOutput {
-    fn fatal_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
-        todo!("Implement fatal_error method")
+    fn game_start(&mut self) -> Result<(), std::io::Error> {
+        Ok(())
     }
 
     fn output_guess(&mut self, guess: &str) -> Result<(), std::io::Error> {
@@ -30,7 +26,28 @@ impl OutputSink for TestOutput {
         Ok(())
     }
 
+    fn won(&mut self, _guesses: u8) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
     fn out_of_guesses(&mut self) -> Result<(), std::io::Error> {
-        todo!("Implement out_of_guesses method")
+        Ok(())
+    }
+
+    fn stumped(&mut self, _reason: &str) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn stored(&mut self, _results: &SessionResults) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    /// The error is returned to the caller of the session, which decides how to report it.
+    fn fatal_error(&mut self, _msg: &str) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn recoverable_error(&mut self, _msg: &str) -> Result<(), std::io::Error> {
+        Ok(())
     }
 }
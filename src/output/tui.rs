@@ -0,0 +1,71 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::output::OutputSink;
+use crate::session::SessionResults;
+use crate::tui::Tui;
+use crate::word_analyzer::Word;
+
+/// Draws session output on the full-screen terminal UI.
+pub struct TuiOutput {
+    tui: Rc<RefCell<Tui>>,
+}
+
+impl TuiOutput {
+    pub fn new(tui: Rc<RefCell<Tui>>) -> Self {
+        TuiOutput { tui }
+    }
+
+    fn show_status(&mut self, msg: String) -> Result<(), std::io::Error> {
+        let mut tui = self.tui.borrow_mut();
+        tui.board.status = msg;
+        tui.draw()
+    }
+}
+
+impl OutputSink for TuiOutput {
+    fn game_start(&mut self) -> Result<(), std::io::Error> {
+        self.show_status("Colour the tiles to match your game, then press enter.".to_string())
+    }
+
+    fn output_guess(&mut self, guess: &str) -> Result<(), std::io::Error> {
+        let mut tui = self.tui.borrow_mut();
+        tui.board.set_guess(guess);
+        tui.draw()
+    }
+
+    fn fatal_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
+        self.show_status(format!("Fatal error: {msg}"))
+    }
+
+    fn recoverable_error(&mut self, msg: &str) -> Result<(), std::io::Error> {
+        self.show_status(format!("{msg}. Please try again."))
+    }
+
+    fn won(&mut self, guesses: u8) -> Result<(), std::io::Error> {
+        self.show_status(format!("Solved in {guesses} guesses!"))
+    }
+
+    fn out_of_guesses(&mut self) -> Result<(), std::io::Error> {
+        self.show_status("Out of guesses!".to_string())
+    }
+
+    fn stumped(&mut self, reason: &str) -> Result<(), std::io::Error> {
+        self.show_status(format!("I am stumped! {reason}"))
+    }
+
+    /// The caller shows the final message and waits for a key before closing the screen.
+    fn stored(&mut self, _results: &SessionResults) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn candidates(&mut self, candidates: &[Word]) -> Result<(), std::io::Error> {
+        self.tui.borrow_mut().board.set_candidates(
+            candidates
+                .iter()
+                .map(|word| (word.as_str(), word.total_probability))
+                .collect(),
+        );
+        Ok(())
+    }
+}
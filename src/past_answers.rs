@@ -0,0 +1,78 @@
+use crate::word_list::{IssueKind, LineIssue, check_word, parse_date, parse_fields};
+use chrono::NaiveDate;
+
+/// A previously used official answer, with the date it was used when known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastAnswer {
+    pub word: String,
+    pub date: Option<NaiveDate>,
+}
+
+/// Parses a past answers file. Each line holds a word, optionally preceded by its `YYYY-MM-DD` date,
+/// separated by whitespace or a comma. Blank lines and `#` comments are skipped and bad lines are reported.
+pub fn parse_past_answers(src: &str) -> (Vec<PastAnswer>, Vec<LineIssue>) {
+    parse_fields(src, |line, fields| {
+        let (date, word) = match fields {
+            [word] => (None, *word),
+            [date, word] => (Some(parse_date(date)?), *word),
+            _ => {
+                return Err((
+                    line.to_string(),
+                    IssueKind::Malformed("should be 'date,word' or 'word'"),
+                ));
+            }
+        };
+        Ok(PastAnswer {
+            word: check_word(word)?,
+            date,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_past_answers() {
+        let (answers, issues) =
+            parse_past_answers("# answers\n2021-06-19 cigar\n2021-06-20,REBUT\nsissy\nab\n");
+        assert_eq!(
+            answers,
+            vec![
+                PastAnswer {
+                    word: "cigar".to_string(),
+                    date: NaiveDate::from_ymd_opt(2021, 6, 19),
+                },
+                PastAnswer {
+                    word: "rebut".to_string(),
+                    date: NaiveDate::from_ymd_opt(2021, 6, 20),
+                },
+                PastAnswer {
+                    word: "sissy".to_string(),
+                    date: None,
+                },
+            ]
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 5);
+    }
+
+    #[test]
+    fn test_bad_dates_and_extra_fields_are_reported() {
+        let (answers, issues) = parse_past_answers("2021-13-01 cigar\n2021-06-20 rebut extra\n");
+        assert!(answers.is_empty());
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| issue.kind.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                IssueKind::Malformed("is not a YYYY-MM-DD date"),
+                IssueKind::Malformed("should be 'date,word' or 'word'"),
+            ]
+        );
+        assert_eq!(issues[0].entry, "2021-13-01");
+        assert_eq!(issues[1].line, 2);
+    }
+}
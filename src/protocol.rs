@@ -0,0 +1,419 @@
+use crate::DB;
+use crate::config::Config;
+use crate::constants::STRATEGY;
+use crate::error::FatalError;
+use crate::input::ApiInput;
+use crate::output::ApiOutput;
+use crate::session::{Session, SessionOutcome, SessionType};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use thiserror::Error;
+
+/// Version of the JSON-lines protocol. Every message carries it in the `v` field.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Messages sent by the program driving crackle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Starts a new game. crackle answers with its first `guess`.
+    NewGame,
+    /// Feedback for the last guess in `gyn` notation.
+    Feedback { feedback: String },
+    /// Reply to `answer_request`. `null` means the answer is not known.
+    Answer { answer: Option<String> },
+    /// Reply to `add_word_request`.
+    AddWord { add: bool },
+    /// Ends the conversation, abandoning any game in progress.
+    Quit,
+}
+
+/// Messages sent by crackle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Sent once when the conversation starts.
+    Ready {
+        strategy: String,
+    },
+    Guess {
+        guess: String,
+    },
+    OutOfGuesses,
+    /// No word in the list fits the feedback.
+    Stumped {
+        message: String,
+    },
+    /// Asks for the real answer after a lost or stumped game.
+    AnswerRequest,
+    /// Asks whether an answer missing from the word list should be added to the custom word list.
+    AddWordRequest {
+        word: String,
+    },
+    /// Sent when a game ends and has been stored, including games the client quit.
+    Result {
+        session_id: String,
+        outcome: SessionOutcome,
+        win: bool,
+        guesses: u8,
+        word: String,
+    },
+    /// A message that could not be handled. Non-fatal errors leave the conversation where it was.
+    Error {
+        message: String,
+        fatal: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<M> {
+    v: u32,
+    #[serde(flatten)]
+    message: M,
+}
+
+#[derive(Deserialize)]
+struct VersionOnly {
+    v: u32,
+}
+
+#[derive(Error, Debug)]
+pub enum ProtocolError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed message: {0}")]
+    Malformed(#[from] serde_json::Error),
+
+    #[error("Unsupported protocol version {0}, expected {PROTOCOL_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// Encodes a message as a single JSON line without the trailing newline.
+pub fn encode<M: Serialize>(message: &M) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&Envelope {
+        v: PROTOCOL_VERSION,
+        message,
+    })
+}
+
+/// Decodes a single JSON line, checking the protocol version before the message itself.
+pub fn decode<M: DeserializeOwned>(line: &str) -> Result<M, ProtocolError> {
+    let VersionOnly { v } = serde_json::from_str(line)?;
+    if v != PROTOCOL_VERSION {
+        return Err(ProtocolError::UnsupportedVersion(v));
+    }
+    let envelope: Envelope<M> = serde_json::from_str(line)?;
+    Ok(envelope.message)
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &ServerMessage) -> std::io::Result<()> {
+    let line = encode(message).map_err(std::io::Error::other)?;
+    writeln!(writer, "{line}")?;
+    writer.flush()
+}
+
+/// Reads the next client message, skipping blank lines. Returns `Ok(None)` at the end of the input.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<ClientMessage>, ProtocolError> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            return decode(line.trim()).map(Some);
+        }
+    }
+}
+
+/// A writer shared by the input and output halves of an API session so their messages stay in order.
+pub struct SharedWriter<W: Write>(Rc<RefCell<W>>);
+
+impl<W: Write> SharedWriter<W> {
+    pub fn new(writer: W) -> Self {
+        SharedWriter(Rc::new(RefCell::new(writer)))
+    }
+}
+
+impl SharedWriter<Vec<u8>> {
+    /// Takes everything written so far, leaving the buffer empty.
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.borrow_mut())
+    }
+}
+
+impl<W: Write> Clone for SharedWriter<W> {
+    fn clone(&self) -> Self {
+        SharedWriter(Rc::clone(&self.0))
+    }
+}
+
+impl<W: Write> Write for SharedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+/// Plays games driven by JSON-lines messages until the client sends `quit` or closes its input.
+/// Each `new_game` runs a full `SessionType::Api` session that is stored like any other.
+pub fn serve<R: BufRead, W: Write>(
+    mut reader: R,
+    writer: W,
+    config: &Config,
+    result_db: &DB,
+    word_db: &DB,
+) -> Result<(), FatalError> {
+    let mut writer = SharedWriter::new(writer);
+    write_message(
+        &mut writer,
+        &ServerMessage::Ready {
+            strategy: STRATEGY.to_string(),
+        },
+    )?;
+
+    loop {
+        match read_message(&mut reader) {
+            Ok(None) | Ok(Some(ClientMessage::Quit)) => return Ok(()),
+            Ok(Some(ClientMessage::NewGame)) => {
+                let mut session = Session::new(
+                    SessionType::Api,
+                    ApiInput::new(&mut reader, writer.clone()),
+                    ApiOutput::new(writer.clone()),
+                    config,
+                    result_db,
+                    word_db,
+                );
+                session.capture_replays(config.replay_dir());
+                session.initialize()?;
+                // The session sends the `result` message itself, and a fatal `error` if it fails.
+                match session.start() {
+                    // The client quit or closed its input in the middle of a game.
+                    Ok(SessionOutcome::Abandoned) => return Ok(()),
+                    Ok(_) => {}
+                    // The client went away while asked for the answer.
+                    Err(FatalError::IOError(_)) => return Ok(()),
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(Some(message)) => write_message(
+                &mut writer,
+                &ServerMessage::Error {
+                    message: format!("Expected new_game or quit, got {message:?}"),
+                    fatal: false,
+                },
+            )?,
+            Err(ProtocolError::Io(e)) => return Err(FatalError::IOError(e)),
+            Err(e) => write_message(
+                &mut writer,
+                &ServerMessage::Error {
+                    message: e.to_string(),
+                    fatal: false,
+                },
+            )?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::SessionFilter;
+    use crate::setup;
+
+    fn round_trip<M>(message: M)
+    where
+        M: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let line = encode(&message).unwrap();
+        assert!(line.contains(r#""v":1"#));
+        assert_eq!(decode::<M>(&line).unwrap(), message);
+    }
+
+    #[test]
+    fn test_client_messages_round_trip() {
+        round_trip(ClientMessage::NewGame);
+        round_trip(ClientMessage::Feedback {
+            feedback: "nnygy".to_string(),
+        });
+        round_trip(ClientMessage::Answer { answer: None });
+        round_trip(ClientMessage::AddWord { add: true });
+        round_trip(ClientMessage::Quit);
+    }
+
+    #[test]
+    fn test_server_messages_round_trip() {
+        round_trip(ServerMessage::Ready {
+            strategy: STRATEGY.to_string(),
+        });
+        round_trip(ServerMessage::Guess {
+            guess: "crane".to_string(),
+        });
+        round_trip(ServerMessage::OutOfGuesses);
+        round_trip(ServerMessage::AnswerRequest);
+        round_trip(ServerMessage::Stumped {
+            message: "No guess found".to_string(),
+        });
+        round_trip(ServerMessage::Result {
+            session_id: "id".to_string(),
+            outcome: SessionOutcome::Won,
+            win: true,
+            guesses: 3,
+            word: "slate".to_string(),
+        });
+        round_trip(ServerMessage::Error {
+            message: "bad".to_string(),
+            fatal: false,
+        });
+    }
+
+    #[test]
+    fn test_decode_checks_version() {
+        assert_eq!(
+            decode::<ClientMessage>(r#"{"v":1,"type":"feedback","feedback":"ggggg"}"#).unwrap(),
+            ClientMessage::Feedback {
+                feedback: "ggggg".to_string()
+            }
+        );
+        assert!(matches!(
+            decode::<ClientMessage>(r#"{"v":2,"type":"quit"}"#),
+            Err(ProtocolError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            decode::<ClientMessage>(r#"{"type":"quit"}"#),
+            Err(ProtocolError::Malformed(_))
+        ));
+    }
+
+    fn play(script: &str, max_guesses: u8) -> (Vec<ServerMessage>, DB) {
+        let config = Config {
+            starting_word_limit: 1,
+            max_guesses,
+            ..Config::default()
+        };
+        let word_db = setup::setup_word_db("crane\nslate\nplate\ngrate\nirate\n").unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        let mut output = Vec::new();
+        serve(
+            script.as_bytes(),
+            &mut output,
+            &config,
+            &result_db,
+            &word_db,
+        )
+        .unwrap();
+
+        let messages = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| decode(line).unwrap())
+            .collect();
+        (messages, result_db)
+    }
+
+    #[test]
+    fn test_serve_full_game() {
+        let script = [
+            r#"{"v":1,"type":"new_game"}"#,
+            r#"{"v":1,"type":"feedback","feedback":"bad"}"#,
+            r#"{"v":1,"type":"feedback","feedback":"ggggg"}"#,
+            r#"{"v":1,"type":"quit"}"#,
+        ]
+        .join("\n");
+        let (messages, result_db) = play(&script, 6);
+
+        assert!(matches!(messages[0], ServerMessage::Ready { .. }));
+        let ServerMessage::Guess { guess } = &messages[1] else {
+            panic!("expected a guess, got {:?}", messages[1]);
+        };
+        assert!(matches!(
+            messages[2],
+            ServerMessage::Error { fatal: false, .. }
+        ));
+        let ServerMessage::Result {
+            win, guesses, word, ..
+        } = &messages[3]
+        else {
+            panic!("expected a result, got {:?}", messages[3]);
+        };
+        assert!(win);
+        assert_eq!(*guesses, 1);
+        assert_eq!(word, guess);
+        assert_eq!(messages.len(), 4);
+
+        let stored = result_db
+            .get_session_results(&SessionFilter::default())
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].session_type, "Api");
+    }
+
+    #[test]
+    fn test_serve_quit_mid_game_is_abandoned() {
+        let script = [
+            r#"{"v":1,"type":"new_game"}"#,
+            r#"{"v":1,"type":"feedback","feedback":"ngggg"}"#,
+            r#"{"v":1,"type":"quit"}"#,
+        ]
+        .join("\n");
+        let (messages, result_db) = play(&script, 6);
+
+        assert!(matches!(
+            messages.last(),
+            Some(ServerMessage::Result {
+                outcome: SessionOutcome::Abandoned,
+                win: false,
+                guesses: 1,
+                ..
+            })
+        ));
+        let stored = result_db
+            .get_session_results(&SessionFilter::default())
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].outcome, SessionOutcome::Abandoned);
+    }
+
+    #[test]
+    fn test_serve_rejects_feedback_before_new_game() {
+        let (messages, _) = play(r#"{"v":1,"type":"feedback","feedback":"ggggg"}"#, 6);
+        assert!(matches!(
+            messages[1],
+            ServerMessage::Error { fatal: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_serve_lost_game_asks_for_answer() {
+        let script = [
+            r#"{"v":1,"type":"new_game"}"#,
+            r#"{"v":1,"type":"feedback","feedback":"nnnnn"}"#,
+            r#"{"v":1,"type":"answer","answer":"zzzzz"}"#,
+            r#"{"v":1,"type":"add_word","add":false}"#,
+        ]
+        .join("\n");
+        let (messages, _) = play(&script, 1);
+
+        assert_eq!(
+            messages[2..5],
+            [
+                ServerMessage::OutOfGuesses,
+                ServerMessage::AnswerRequest,
+                ServerMessage::AddWordRequest {
+                    word: "zzzzz".to_string()
+                },
+            ]
+        );
+        assert!(matches!(
+            &messages[5],
+            ServerMessage::Result { win: false, word, .. } if word == "zzzzz"
+        ));
+    }
+}
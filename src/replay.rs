@@ -0,0 +1,336 @@
+use crate::DB;
+use crate::config::PastAnswerMode;
+use crate::constants::{STRATEGY, WORD_LENGTH};
+use crate::game_engine::{GameEngine, score_guess};
+use crate::input::check_input;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the replay file format. Files with a different version are refused.
+pub const REPLAY_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Database error: {0}")]
+    Rusqlite(#[from] rusqlite::Error),
+    #[error("Word source error: {0}")]
+    Setup(#[from] crate::setup::SetupError),
+    #[error("Invalid replay file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported replay version {0}, expected {REPLAY_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("Invalid feedback '{feedback}' on turn {turn}")]
+    InvalidFeedback { turn: usize, feedback: String },
+    #[error("Invalid guess '{guess}' on turn {turn}, expected {WORD_LENGTH} lowercase letters")]
+    InvalidGuess { turn: usize, guess: String },
+}
+
+/// One recorded turn and the engine state it led to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTurn {
+    pub guess: String,
+    pub feedback: String,
+    /// Known letters after the feedback, e.g. `__a_e`. Missing for turns played before the session was resumed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Candidates left after the feedback. Missing for turns played before the session was resumed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidates: Option<usize>,
+}
+
+/// Everything needed to re-run a game outside the session that played it, written when the solver is stumped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub version: u32,
+    pub crackle_version: String,
+    pub session_id: String,
+    pub created: String,
+    /// Why the replay was captured, such as the stumped error.
+    pub reason: String,
+    pub strategy: String,
+    /// Name of the word source, or `builtin`.
+    pub word_source: String,
+    pub word_source_hash: String,
+    /// Seed of the session's random choices, when it was seeded.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub past_answer_mode: PastAnswerMode,
+    pub past_answer_weight: f64,
+    /// Past answers the engine was given. Empty when `past_answer_mode` is `ignore`.
+    #[serde(default)]
+    pub past_answers: Vec<String>,
+    /// The real answer, when the player gave it.
+    #[serde(default)]
+    pub secret: Option<String>,
+    pub turns: Vec<ReplayTurn>,
+}
+
+impl ReplayFile {
+    /// Writes the file as `<session_id>.json` in `dir`, creating the directory if needed, and returns its path.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, ReplayError> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.json", self.session_id));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let replay: ReplayFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(replay.version));
+        }
+        Ok(replay)
+    }
+}
+
+/// What the engine did on one replayed turn, with any differences from the recording.
+#[derive(Debug, Serialize)]
+pub struct ReplayStep {
+    pub turn: usize,
+    pub guess: String,
+    pub feedback: String,
+    pub pattern: String,
+    pub candidates: usize,
+    pub divergences: Vec<String>,
+}
+
+/// The result of re-running a replay file against the current engine and word list.
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    /// Differences in setup, such as another word list, that make divergence expected.
+    pub warnings: Vec<String>,
+    pub steps: Vec<ReplayStep>,
+    /// The guess the engine picks after the last turn. `None` when it is solved or stumped.
+    pub next_guess: Option<String>,
+    pub stumped: bool,
+}
+
+impl ReplayReport {
+    /// The first turn whose engine state differs from the recording.
+    pub fn first_divergence(&self) -> Option<&ReplayStep> {
+        self.steps.iter().find(|step| !step.divergences.is_empty())
+    }
+}
+
+/// Re-runs the recorded turns one by one, comparing the engine's guesses, known letters and candidate counts with the recording.
+/// Recorded guesses are always played, so a single divergence does not hide later ones.
+pub fn replay(
+    file: &ReplayFile,
+    word_db: &DB,
+    word_source_hash: &str,
+) -> Result<ReplayReport, ReplayError> {
+    let mut warnings = Vec::new();
+    if file.strategy != STRATEGY {
+        warnings.push(format!(
+            "Recorded with strategy '{}', replaying with '{STRATEGY}'",
+            file.strategy
+        ));
+    }
+    if file.word_source_hash != word_source_hash {
+        warnings.push(format!(
+            "Recorded with a different '{}' word list than the active one",
+            file.word_source
+        ));
+    }
+
+    let mut engine = GameEngine::new();
+    engine.set_past_answers(
+        file.past_answers.iter().cloned().collect(),
+        file.past_answer_mode,
+        file.past_answer_weight,
+    );
+    let mut steps = Vec::new();
+    let mut next_guess = None;
+    let mut stumped = false;
+
+    for (i, turn) in file.turns.iter().enumerate() {
+        let number = i + 1;
+        if turn.guess.len() != WORD_LENGTH || !turn.guess.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(ReplayError::InvalidGuess {
+                turn: number,
+                guess: turn.guess.clone(),
+            });
+        }
+        check_input(&turn.feedback).map_err(|_| ReplayError::InvalidFeedback {
+            turn: number,
+            feedback: turn.feedback.clone(),
+        })?;
+        let mut divergences = Vec::new();
+        if i > 0 && stumped {
+            divergences.push(format!(
+                "The engine was stumped after turn {i}, the recording guessed '{}'",
+                turn.guess
+            ));
+        } else if i > 0 && engine.get_current_guess() != turn.guess {
+            divergences.push(format!(
+                "The engine guessed '{}', the recording guessed '{}'",
+                engine.get_current_guess(),
+                turn.guess
+            ));
+        }
+        if let Some(secret) = &file.secret {
+            match score_guess(&turn.guess, secret) {
+                Some(expected) if expected == turn.feedback => {}
+                Some(expected) => divergences.push(format!(
+                    "Feedback '{}' does not match the secret '{secret}', which scores '{expected}'",
+                    turn.feedback
+                )),
+                None => divergences.push(format!(
+                    "The secret '{secret}' cannot be scored against '{}'",
+                    turn.guess
+                )),
+            }
+        }
+
+        engine.set_starting_word(turn.guess.clone());
+        engine.parse_input(&turn.feedback);
+        let pattern = engine.get_pattern();
+        if let Some(recorded) = &turn.pattern
+            && *recorded != pattern
+        {
+            divergences.push(format!(
+                "Known letters are '{pattern}', the recording has '{recorded}'"
+            ));
+        }
+
+        let ranked = if engine.check_for_win() {
+            None
+        } else {
+            Some(engine.rank_candidates(word_db.filter_words(&pattern)?))
+        };
+        let candidates = ranked.as_ref().map_or(1, Vec::len);
+        if let Some(recorded) = turn.candidates
+            && recorded != candidates
+        {
+            divergences.push(format!(
+                "{candidates} candidates are left, the recording had {recorded}"
+            ));
+        }
+
+        (next_guess, stumped) = match &ranked {
+            None => (None, false),
+            Some(ranked) => match engine.choose_guess(ranked) {
+                Ok(guess) => (Some(guess), false),
+                Err(_) => (None, true),
+            },
+        };
+        steps.push(ReplayStep {
+            turn: number,
+            guess: turn.guess.clone(),
+            feedback: turn.feedback.clone(),
+            pattern,
+            candidates,
+            divergences,
+        });
+    }
+
+    Ok(ReplayReport {
+        warnings,
+        steps,
+        next_guess,
+        stumped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup;
+
+    const WORDS: &str = "crane\nslate\ngrate\nirate\nplate";
+
+    fn turn(guess: &str, feedback: &str, candidates: Option<usize>) -> ReplayTurn {
+        ReplayTurn {
+            guess: guess.to_string(),
+            feedback: feedback.to_string(),
+            pattern: None,
+            candidates,
+        }
+    }
+
+    fn replay_file(turns: Vec<ReplayTurn>, secret: Option<&str>) -> ReplayFile {
+        ReplayFile {
+            version: REPLAY_VERSION,
+            crackle_version: env!("CARGO_PKG_VERSION").to_string(),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            created: "2026-10-18T12:00:00+00:00".to_string(),
+            reason: "No guess found".to_string(),
+            strategy: STRATEGY.to_string(),
+            word_source: "builtin".to_string(),
+            word_source_hash: "hash".to_string(),
+            seed: None,
+            past_answer_mode: PastAnswerMode::Ignore,
+            past_answer_weight: 0.5,
+            past_answers: Vec::new(),
+            secret: secret.map(str::to_string),
+            turns,
+        }
+    }
+
+    #[test]
+    fn test_replay_matches_recording() {
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let file = replay_file(vec![turn("crane", "ngggg", Some(0))], None);
+
+        let report = replay(&file, &word_db, "hash").unwrap();
+        assert!(report.warnings.is_empty());
+        assert!(report.first_divergence().is_none());
+        assert_eq!(report.steps[0].pattern, "_rane");
+        assert!(report.stumped);
+    }
+
+    #[test]
+    fn test_replay_reports_first_divergence() {
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let file = replay_file(
+            vec![
+                turn("slate", "nnggg", Some(5)),
+                turn("plate", "nnggg", None),
+            ],
+            Some("grate"),
+        );
+
+        let report = replay(&file, &word_db, "other").unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        let step = report.first_divergence().unwrap();
+        assert_eq!(step.turn, 1);
+        assert_eq!(step.divergences.len(), 1);
+        assert!(step.divergences[0].contains("candidates"));
+        assert!(report.steps[1].divergences[0].contains("The engine guessed"));
+    }
+
+    #[test]
+    fn test_replay_rejects_invalid_guesses() {
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        for guess in ["cran", "CRANE", "cr4ne", "cranés"] {
+            let file = replay_file(
+                vec![turn("slate", "nnggg", None), turn(guess, "nnnnn", None)],
+                None,
+            );
+            assert!(matches!(
+                replay(&file, &word_db, "hash"),
+                Err(ReplayError::InvalidGuess { turn: 2, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("crackle-replays-{}", uuid::Uuid::new_v4()));
+        let file = replay_file(vec![turn("crane", "nnygy", Some(3))], Some("slate"));
+        let path = file.save(&dir).unwrap();
+        assert_eq!(ReplayFile::load(&path).unwrap(), file);
+
+        let mut old = file.clone();
+        old.version = REPLAY_VERSION + 1;
+        let path = old.save(&dir).unwrap();
+        assert!(matches!(
+            ReplayFile::load(&path),
+            Err(ReplayError::UnsupportedVersion(_))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,534 @@
+use crate::database::SessionFilter;
+use crate::session::{SessionOutcome, SessionResults, SessionType};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Width of the longest bar drawn in the guess histogram.
+const HISTOGRAM_WIDTH: u32 = 30;
+
+/// Aggregated statistics for a group of stored sessions.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    /// Finished games. Abandoned games are counted separately and do not count as losses.
+    pub games: u32,
+    pub wins: u32,
+    pub abandoned: u32,
+    /// Number of won games keyed by the number of guesses it took.
+    pub guess_histogram: BTreeMap<u8, u32>,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Average number of guesses across won games.
+    pub average_guesses: Option<f64>,
+}
+
+impl Stats {
+    /// Builds statistics from sessions ordered from oldest to newest.
+    pub fn from_sessions<'s>(sessions: impl IntoIterator<Item = &'s SessionResults>) -> Self {
+        let mut stats = Stats::default();
+        let mut streak = 0;
+        let mut total_guesses = 0u32;
+
+        for session in sessions {
+            if session.outcome == SessionOutcome::Abandoned {
+                stats.abandoned += 1;
+                continue;
+            }
+            stats.games += 1;
+            if session.win {
+                stats.wins += 1;
+                total_guesses += session.number_of_guesses as u32;
+                *stats
+                    .guess_histogram
+                    .entry(session.number_of_guesses)
+                    .or_insert(0) += 1;
+                streak += 1;
+                stats.longest_streak = stats.longest_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+        }
+
+        stats.current_streak = streak;
+        if stats.wins > 0 {
+            stats.average_guesses = Some(total_guesses as f64 / stats.wins as f64);
+        }
+        stats
+    }
+
+    pub fn losses(&self) -> u32 {
+        self.games - self.wins
+    }
+
+    /// Win rate as a percentage, or zero when no games have been played.
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.wins as f64 * 100.0 / self.games as f64
+    }
+
+    /// The statistics with the derived `losses` and `win_rate` included.
+    pub fn to_json(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["losses"] = json!(self.losses());
+        value["win_rate"] = json!(self.win_rate());
+        value
+    }
+}
+
+/// Whether a session was played by a person, so it counts towards their streaks, win rate and rank.
+/// Test and archive games are played by the solver on its own and are only reported by session type.
+fn is_player_session(session: &SessionResults) -> bool {
+    session.session_type == SessionType::Interactive.as_str()
+        || session.session_type == SessionType::Api.as_str()
+}
+
+/// Sessions that count towards player statistics. When `filter` picks a session type, every session is kept.
+fn player_sessions<'s>(
+    filter: &SessionFilter,
+    sessions: &'s [SessionResults],
+) -> impl Iterator<Item = &'s SessionResults> {
+    let all = filter.session_type.is_some();
+    sessions
+        .iter()
+        .filter(move |session| all || is_player_session(session))
+}
+
+/// A statistics report over `session_results`, split by session type.
+/// `overall` only counts player sessions, unless the filter picks a session type.
+#[derive(Debug)]
+pub struct Report {
+    pub filter: SessionFilter,
+    pub overall: Stats,
+    pub by_session_type: BTreeMap<String, Stats>,
+}
+
+impl Report {
+    /// Builds a report from sessions that were already read with `filter`, ordered from oldest to newest.
+    pub fn new(filter: SessionFilter, sessions: &[SessionResults]) -> Self {
+        let mut grouped: BTreeMap<String, Vec<&SessionResults>> = BTreeMap::new();
+        for session in sessions {
+            grouped
+                .entry(session.session_type.clone())
+                .or_default()
+                .push(session);
+        }
+
+        Report {
+            overall: Stats::from_sessions(player_sessions(&filter, sessions)),
+            by_session_type: grouped
+                .into_iter()
+                .map(|(session_type, sessions)| (session_type, Stats::from_sessions(sessions)))
+                .collect(),
+            filter,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let by_session_type: serde_json::Map<String, Value> = self
+            .by_session_type
+            .iter()
+            .map(|(session_type, stats)| (session_type.clone(), stats.to_json()))
+            .collect();
+        json!({
+            "filter": filter_json(&self.filter),
+            "overall": self.overall.to_json(),
+            "by_session_type": by_session_type,
+        })
+    }
+
+    /// Renders the report with terminal colours.
+    pub fn render_colored(&self) -> String {
+        self.render(true)
+    }
+
+    /// Renders the report without any escape codes, suitable for saving to a file.
+    pub fn render_plain(&self) -> String {
+        self.render(false)
+    }
+
+    fn render(&self, color: bool) -> String {
+        let mut out = String::new();
+        let heading = |text: &str| {
+            if color {
+                text.bold().cyan().to_string()
+            } else {
+                text.to_string()
+            }
+        };
+
+        let _ = writeln!(out, "{}", heading("Crackle Statistics Report"));
+        if let Some(profile) = &self.filter.profile {
+            let _ = writeln!(out, "Profile: {profile}");
+        }
+        let _ = writeln!(out, "Date range: {}", date_range(&self.filter));
+        let _ = writeln!(out);
+
+        let _ = writeln!(out, "{}", heading("Overall"));
+        render_stats(&mut out, &self.overall, color);
+
+        for (session_type, stats) in &self.by_session_type {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "{}", heading(&format!("{session_type} sessions")));
+            render_stats(&mut out, stats, color);
+        }
+        out
+    }
+}
+
+/// Statistics for every profile side by side, ranked by win rate and then by average guesses.
+/// Only player sessions are ranked, unless the filter picks a session type.
+#[derive(Debug)]
+pub struct Leaderboard {
+    pub filter: SessionFilter,
+    pub entries: Vec<(String, Stats)>,
+}
+
+impl Leaderboard {
+    /// Builds a leaderboard from sessions of all profiles, ordered from oldest to newest.
+    pub fn new(filter: SessionFilter, sessions: &[SessionResults]) -> Self {
+        let mut grouped: BTreeMap<String, Vec<&SessionResults>> = BTreeMap::new();
+        for session in player_sessions(&filter, sessions) {
+            grouped
+                .entry(session.profile.clone())
+                .or_default()
+                .push(session);
+        }
+
+        let mut entries: Vec<(String, Stats)> = grouped
+            .into_iter()
+            .map(|(profile, sessions)| (profile, Stats::from_sessions(sessions)))
+            .collect();
+        entries.sort_by(|(_, a), (_, b)| {
+            b.win_rate().total_cmp(&a.win_rate()).then_with(|| {
+                a.average_guesses
+                    .unwrap_or(f64::MAX)
+                    .total_cmp(&b.average_guesses.unwrap_or(f64::MAX))
+            })
+        });
+        Leaderboard { filter, entries }
+    }
+
+    /// Entries in rank order, each holding its `profile` alongside the statistics.
+    pub fn to_json(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|(profile, stats)| {
+                let mut entry = stats.to_json();
+                entry["profile"] = json!(profile);
+                entry
+            })
+            .collect();
+        json!({
+            "filter": filter_json(&self.filter),
+            "entries": entries,
+        })
+    }
+
+    pub fn render_colored(&self) -> String {
+        self.render(true)
+    }
+
+    pub fn render_plain(&self) -> String {
+        self.render(false)
+    }
+
+    fn render(&self, color: bool) -> String {
+        let mut out = String::new();
+        let title = "Crackle Leaderboard";
+        let _ = writeln!(
+            out,
+            "{}",
+            if color {
+                title.bold().cyan().to_string()
+            } else {
+                title.to_string()
+            }
+        );
+        let _ = writeln!(out, "Date range: {}", date_range(&self.filter));
+        let _ = writeln!(out);
+
+        if self.entries.is_empty() {
+            let _ = writeln!(out, "  No games recorded.");
+            return out;
+        }
+        let _ = writeln!(
+            out,
+            "  {:<4}{:<16}{:>7}{:>7}{:>10}{:>10}{:>9}",
+            "#", "Profile", "Games", "Wins", "Win rate", "Average", "Streak"
+        );
+        for (rank, (profile, stats)) in self.entries.iter().enumerate() {
+            let average = stats
+                .average_guesses
+                .map_or("-".to_string(), |avg| format!("{avg:.2}"));
+            let _ = writeln!(
+                out,
+                "  {:<4}{:<16}{:>7}{:>7}{:>9.1}%{:>10}{:>9}",
+                rank + 1,
+                profile,
+                stats.games,
+                stats.wins,
+                stats.win_rate(),
+                average,
+                stats.current_streak
+            );
+        }
+        out
+    }
+}
+
+fn filter_json(filter: &SessionFilter) -> Value {
+    let date = |d: &Option<chrono::DateTime<chrono::Utc>>| d.map(|d| d.to_rfc3339());
+    json!({
+        "since": date(&filter.since),
+        "until": date(&filter.until),
+        "session_type": filter.session_type,
+        "profile": filter.profile,
+    })
+}
+
+fn date_range(filter: &SessionFilter) -> String {
+    let fmt = |d: &chrono::DateTime<chrono::Utc>| d.format("%Y-%m-%d").to_string();
+    match (&filter.since, &filter.until) {
+        (None, None) => "all time".to_string(),
+        (Some(since), None) => format!("{} to now", fmt(since)),
+        (None, Some(until)) => format!("up to {}", fmt(until)),
+        (Some(since), Some(until)) => format!("{} to {}", fmt(since), fmt(until)),
+    }
+}
+
+fn render_stats(out: &mut String, stats: &Stats, color: bool) {
+    if stats.games == 0 {
+        let _ = writeln!(out, "  No games recorded.");
+        if stats.abandoned > 0 {
+            let _ = writeln!(out, "  Abandoned: {}", stats.abandoned);
+        }
+        return;
+    }
+
+    let win_rate = format!("{:.1}%", stats.win_rate());
+    let win_rate = if !color {
+        win_rate
+    } else if stats.win_rate() >= 50.0 {
+        win_rate.green().to_string()
+    } else {
+        win_rate.red().to_string()
+    };
+    let average = stats
+        .average_guesses
+        .map_or("-".to_string(), |avg| format!("{avg:.2}"));
+
+    let _ = writeln!(
+        out,
+        "  Games: {}  Wins: {}  Losses: {}  Win rate: {win_rate}",
+        stats.games,
+        stats.wins,
+        stats.losses()
+    );
+    if stats.abandoned > 0 {
+        let _ = writeln!(out, "  Abandoned: {}", stats.abandoned);
+    }
+    let _ = writeln!(out, "  Average guesses (wins): {average}");
+    let _ = writeln!(
+        out,
+        "  Current streak: {}  Longest streak: {}",
+        stats.current_streak, stats.longest_streak
+    );
+    let _ = writeln!(out, "  Guess distribution:");
+
+    let largest = stats
+        .guess_histogram
+        .values()
+        .copied()
+        .chain(std::iter::once(stats.losses()))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let bar = |count: u32| {
+        let len = (count * HISTOGRAM_WIDTH).div_ceil(largest) as usize;
+        "#".repeat(len)
+    };
+
+    for (guesses, count) in &stats.guess_histogram {
+        let bars = if color {
+            bar(*count).green().to_string()
+        } else {
+            bar(*count)
+        };
+        let _ = writeln!(out, "    {guesses}: {bars} {count}");
+    }
+    let bars = if color {
+        bar(stats.losses()).red().to_string()
+    } else {
+        bar(stats.losses())
+    };
+    let _ = writeln!(out, "    X: {bars} {}", stats.losses());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn session(session_type: &str, guesses: u8, win: bool) -> SessionResults {
+        profile_session("default", session_type, guesses, win)
+    }
+
+    fn profile_session(
+        profile: &str,
+        session_type: &str,
+        guesses: u8,
+        win: bool,
+    ) -> SessionResults {
+        SessionResults {
+            session_id: Uuid::new_v4(),
+            start_date: chrono::Utc::now(),
+            end_date: chrono::Utc::now(),
+            session_type: session_type.to_string(),
+            word: "crane".to_string(),
+            number_of_guesses: guesses,
+            win,
+            profile: profile.to_string(),
+            outcome: if win {
+                SessionOutcome::Won
+            } else {
+                SessionOutcome::Lost
+            },
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn test_stats_streaks_and_average() {
+        let sessions = vec![
+            session("Test", 3, true),
+            session("Test", 4, true),
+            session("Test", 6, false),
+            session("Test", 2, true),
+            session("Test", 5, true),
+            session("Test", 5, true),
+        ];
+        let stats = Stats::from_sessions(&sessions);
+
+        assert_eq!(stats.games, 6);
+        assert_eq!(stats.wins, 5);
+        assert_eq!(stats.losses(), 1);
+        assert_eq!(stats.current_streak, 3);
+        assert_eq!(stats.longest_streak, 3);
+        assert_eq!(stats.average_guesses, Some(19.0 / 5.0));
+        assert_eq!(stats.guess_histogram.get(&5), Some(&2));
+        assert_eq!(stats.guess_histogram.get(&6), None);
+    }
+
+    #[test]
+    fn test_stats_skip_abandoned_games() {
+        let mut abandoned = session("Interactive", 2, false);
+        abandoned.outcome = SessionOutcome::Abandoned;
+        let sessions = vec![
+            session("Interactive", 3, true),
+            abandoned,
+            session("Interactive", 4, true),
+        ];
+        let stats = Stats::from_sessions(&sessions);
+
+        assert_eq!(stats.games, 2);
+        assert_eq!(stats.abandoned, 1);
+        assert_eq!(stats.losses(), 0);
+        assert_eq!(stats.current_streak, 2);
+        assert!(
+            Report::new(SessionFilter::default(), &sessions)
+                .render_plain()
+                .contains("Abandoned: 1")
+        );
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = Stats::from_sessions(&[]);
+        assert_eq!(stats.win_rate(), 0.0);
+        assert_eq!(stats.average_guesses, None);
+    }
+
+    #[test]
+    fn test_report_splits_by_session_type() {
+        let sessions = vec![
+            session("Interactive", 3, true),
+            session("Test", 4, false),
+            session("Test", 2, true),
+        ];
+        let report = Report::new(SessionFilter::default(), &sessions);
+
+        assert_eq!(report.overall.games, 1);
+        assert_eq!(report.overall.current_streak, 1);
+        assert_eq!(report.by_session_type["Interactive"].games, 1);
+        assert_eq!(report.by_session_type["Test"].wins, 1);
+
+        let filter = SessionFilter {
+            session_type: Some("Test".to_string()),
+            ..SessionFilter::default()
+        };
+        assert_eq!(Report::new(filter, &sessions[1..]).overall.games, 2);
+    }
+
+    #[test]
+    fn test_render_plain_has_no_escape_codes() {
+        let sessions = vec![session("Test", 3, true), session("Test", 6, false)];
+        let text = Report::new(SessionFilter::default(), &sessions).render_plain();
+
+        assert!(!text.contains('\x1b'));
+        assert!(text.contains("Win rate: 50.0%"));
+        assert!(text.contains("Test sessions"));
+        assert!(text.contains("Date range: all time"));
+    }
+
+    #[test]
+    fn test_leaderboard_ranks_profiles() {
+        let sessions = vec![
+            profile_session("ana", "Interactive", 4, true),
+            profile_session("ben", "Interactive", 3, true),
+            profile_session("ana", "Api", 6, false),
+            profile_session("ben", "Interactive", 5, true),
+            profile_session("cal", "Interactive", 2, true),
+            profile_session("ana", "Test", 2, true),
+            profile_session("ana", "Archive", 2, true),
+            profile_session("dee", "Test", 1, true),
+        ];
+        let leaderboard = Leaderboard::new(SessionFilter::default(), &sessions);
+        let ranking: Vec<&str> = leaderboard
+            .entries
+            .iter()
+            .map(|(profile, _)| profile.as_str())
+            .collect();
+
+        assert_eq!(ranking, vec!["cal", "ben", "ana"]);
+        assert_eq!(leaderboard.entries[2].1.games, 2);
+        assert!(leaderboard.render_plain().contains("ben"));
+        assert_eq!(leaderboard.to_json()["entries"][0]["profile"], "cal");
+    }
+
+    #[test]
+    fn test_report_json() {
+        let sessions = vec![
+            profile_session("ana", "Api", 4, true),
+            profile_session("ana", "Interactive", 6, false),
+            profile_session("ana", "Test", 3, true),
+        ];
+        let filter = SessionFilter {
+            profile: Some("ana".to_string()),
+            ..SessionFilter::default()
+        };
+        let json = Report::new(filter, &sessions).to_json();
+
+        assert_eq!(json["filter"]["profile"], "ana");
+        assert_eq!(json["filter"]["since"], Value::Null);
+        assert_eq!(json["overall"]["games"], 2);
+        assert_eq!(json["overall"]["losses"], 1);
+        assert_eq!(json["overall"]["win_rate"], 50.0);
+        assert_eq!(json["overall"]["guess_histogram"]["4"], 1);
+        assert_eq!(json["by_session_type"]["Interactive"]["wins"], 0);
+    }
+}
@@ -0,0 +1,727 @@
+use crate::DB;
+use crate::config::{Config, PastAnswerMode};
+use crate::constants::STRATEGY;
+use crate::database::SessionFilter;
+use crate::error::FatalError;
+use crate::input::{HttpInput, HttpReply, check_input};
+use crate::output::ApiOutput;
+use crate::protocol::{ServerMessage, SharedWriter, decode};
+use crate::report::{Leaderboard, Report};
+use crate::session::{Session, SessionType};
+use crate::suggest::{self, DEFAULT_CANDIDATE_LIMIT};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Address `crackle serve` listens on unless `--addr` is given. Only reachable from this machine.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8787";
+
+/// Games without a request for this long are dropped the next time a game is started.
+const IDLE_GAME_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Error, Debug)]
+pub enum ServerError {
+    #[error("Could not listen on {addr}: {message}")]
+    Bind { addr: String, message: String },
+
+    #[error(transparent)]
+    Fatal(#[from] FatalError),
+}
+
+/// Methods and request headers a browser may use once its origin is allowed.
+const ALLOWED_METHODS: &str = "GET, POST, DELETE, OPTIONS";
+const ALLOWED_HEADERS: &str = "Content-Type";
+
+/// The parts of an HTTP request the solver looks at.
+#[derive(Debug, Default)]
+pub struct Request<'r> {
+    pub method: &'r str,
+    /// The path with an optional query string.
+    pub url: &'r str,
+    /// The `Origin` header, sent by browsers on cross-origin requests.
+    pub origin: Option<&'r str>,
+    pub content_type: Option<&'r str>,
+    pub body: &'r str,
+}
+
+/// Status code, JSON body and extra headers of a reply.
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    /// Sent as JSON, except on a `204` which has no body.
+    pub body: Value,
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Response {
+            status: 200,
+            body,
+            headers: Vec::new(),
+        }
+    }
+
+    fn error(status: u16, message: impl std::fmt::Display) -> Self {
+        Response {
+            status,
+            body: json!({ "error": message.to_string() }),
+            headers: Vec::new(),
+        }
+    }
+
+    fn into_http(self) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+        let header = |name: &str, value: &str| {
+            tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+        };
+        let mut response = if self.status == 204 {
+            tiny_http::Response::from_data(Vec::new())
+        } else {
+            tiny_http::Response::from_data(self.body.to_string())
+                .with_header(header("Content-Type", "application/json"))
+        }
+        .with_status_code(self.status);
+        for (name, value) in &self.headers {
+            response.add_header(header(name, value));
+        }
+        response
+    }
+}
+
+/// Whether a `Content-Type` header names JSON, ignoring parameters such as the charset.
+fn is_json(content_type: Option<&str>) -> bool {
+    content_type
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
+#[derive(Deserialize)]
+struct FeedbackRequest {
+    feedback: String,
+    /// The real answer, used if this feedback ends the game without a win.
+    #[serde(default)]
+    answer: Option<String>,
+    /// Whether an answer missing from the word list is added to the custom word list.
+    #[serde(default)]
+    add_word: bool,
+}
+
+type HttpSession<'a> = Session<'a, 'a, HttpInput, ApiOutput<SharedWriter<Vec<u8>>>>;
+
+struct Game<'a> {
+    session: HttpSession<'a>,
+    /// Protocol messages the session wrote during the current request.
+    events: SharedWriter<Vec<u8>>,
+    reply: Rc<RefCell<HttpReply>>,
+    last_used: Instant,
+}
+
+impl Game<'_> {
+    fn take_events(&self) -> Vec<Value> {
+        String::from_utf8_lossy(&self.events.take())
+            .lines()
+            .filter_map(|line| decode::<ServerMessage>(line).ok())
+            .filter_map(|message| serde_json::to_value(message).ok())
+            .collect()
+    }
+}
+
+/// Routes HTTP requests to games in progress, suggestions and stored statistics.
+/// The word database is loaded once and shared by every request.
+pub struct Solver<'a> {
+    config: &'a Config,
+    result_db: &'a DB,
+    word_db: &'a DB,
+    games: HashMap<Uuid, Game<'a>>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(config: &'a Config, result_db: &'a DB, word_db: &'a DB) -> Self {
+        Solver {
+            config,
+            result_db,
+            word_db,
+            games: HashMap::new(),
+        }
+    }
+
+    /// Handles one request. Pages from an origin in `allowed_origins` may read the reply; other pages get no CORS headers,
+    /// so browsers keep them from reading replies or sending anything but simple requests, which are refused below.
+    pub fn handle(&mut self, request: &Request) -> Response {
+        let allowed_origin = request.origin.filter(|origin| {
+            self.config
+                .allowed_origins
+                .iter()
+                .any(|allowed| allowed == origin)
+        });
+        let mut response = self.route(request);
+        if let Some(origin) = allowed_origin {
+            response
+                .headers
+                .push(("Access-Control-Allow-Origin", origin.to_string()));
+            response.headers.push(("Vary", "Origin".to_string()));
+        }
+        response
+    }
+
+    fn route(&mut self, request: &Request) -> Response {
+        let Request {
+            method, url, body, ..
+        } = *request;
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query = parse_query(query);
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let known = matches!(
+            segments.first(),
+            Some(&("health" | "games" | "suggestions" | "stats" | "leaderboard"))
+        );
+
+        // Plain text and form posts can be sent by any web page without a preflight, so only JSON is accepted.
+        if method == "POST" && known && !is_json(request.content_type) {
+            return Response::error(
+                415,
+                "POST requests must have a Content-Type of application/json",
+            );
+        }
+
+        match (method, segments.as_slice()) {
+            ("OPTIONS", _) if known => Response {
+                status: 204,
+                body: Value::Null,
+                headers: vec![
+                    ("Access-Control-Allow-Methods", ALLOWED_METHODS.to_string()),
+                    ("Access-Control-Allow-Headers", ALLOWED_HEADERS.to_string()),
+                    ("Access-Control-Max-Age", "600".to_string()),
+                ],
+            },
+            ("GET", ["health"]) => Response::ok(json!({
+                "status": "ok",
+                "strategy": STRATEGY,
+                "games": self.games.len(),
+            })),
+            ("POST", ["games"]) => self.new_game(),
+            ("GET", ["games", id]) => self.with_game_id(id, Self::get_game),
+            ("POST", ["games", id, "feedback"]) => {
+                self.with_game_id(id, |solver, id| solver.submit_feedback(id, body))
+            }
+            ("DELETE", ["games", id]) => {
+                self.with_game_id(id, |solver, id| match solver.games.remove(&id) {
+                    Some(mut game) => match game.session.abandon() {
+                        Ok(outcome) => Response::ok(json!({
+                            "game_id": id.to_string(),
+                            "abandoned": true,
+                            "outcome": outcome,
+                        })),
+                        Err(e) => Response::error(500, e),
+                    },
+                    None => Response::error(404, format!("No game {id}")),
+                })
+            }
+            ("GET", ["suggestions"]) => self.suggestions(&query),
+            ("GET", ["stats"]) => self.stats(&query, false),
+            ("GET", ["leaderboard"]) => self.stats(&query, true),
+            _ if known => Response::error(405, format!("{method} is not supported for {path}")),
+            _ => Response::error(404, format!("No route for {path}")),
+        }
+    }
+
+    fn with_game_id(&mut self, id: &str, f: impl FnOnce(&mut Self, Uuid) -> Response) -> Response {
+        match Uuid::parse_str(id) {
+            Ok(id) => f(self, id),
+            Err(_) => Response::error(404, format!("No game {id}")),
+        }
+    }
+
+    fn new_game(&mut self) -> Response {
+        self.evict_idle_games();
+
+        let events = SharedWriter::new(Vec::new());
+        let reply = Rc::new(RefCell::new(HttpReply::default()));
+        let mut session = Session::new(
+            SessionType::Api,
+            HttpInput::new(Rc::clone(&reply)),
+            ApiOutput::new(events.clone()),
+            self.config,
+            self.result_db,
+            self.word_db,
+        );
+        session.capture_replays(self.config.replay_dir());
+        if let Err(e) = session.initialize() {
+            return Response::error(500, e);
+        }
+
+        let id = session.session_id();
+        let guess = session.current_guess().to_string();
+        self.games.insert(
+            id,
+            Game {
+                session,
+                events,
+                reply,
+                last_used: Instant::now(),
+            },
+        );
+        Response::ok(json!({ "game_id": id.to_string(), "guess": guess }))
+    }
+
+    /// Drops games nobody has played for `IDLE_GAME_TIMEOUT`, storing each as abandoned.
+    fn evict_idle_games(&mut self) {
+        let idle: Vec<Uuid> = self
+            .games
+            .iter()
+            .filter(|(_, game)| game.last_used.elapsed() >= IDLE_GAME_TIMEOUT)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in idle {
+            if let Some(mut game) = self.games.remove(&id)
+                && let Err(e) = game.session.abandon()
+            {
+                // A stale game that cannot be stored should not stop a new one from starting.
+                eprintln!("Could not store abandoned game {id}: {e}");
+            }
+        }
+    }
+
+    fn get_game(&mut self, id: Uuid) -> Response {
+        let Some(game) = self.games.get(&id) else {
+            return Response::error(404, format!("No game {id}"));
+        };
+        let history: Vec<Value> = game
+            .session
+            .guess_history()
+            .into_iter()
+            .filter_map(|record| serde_json::to_value(record).ok())
+            .collect();
+        Response::ok(json!({
+            "game_id": id.to_string(),
+            "guess": game.session.current_guess(),
+            "history": history,
+        }))
+    }
+
+    fn submit_feedback(&mut self, id: Uuid, body: &str) -> Response {
+        let request: FeedbackRequest = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(e) => return Response::error(400, e),
+        };
+        let feedback = request.feedback.to_lowercase();
+        if let Err(e) = check_input(&feedback) {
+            return Response::error(400, e);
+        }
+        let Some(game) = self.games.get_mut(&id) else {
+            return Response::error(404, format!("No game {id}"));
+        };
+
+        *game.reply.borrow_mut() = HttpReply {
+            answer: request.answer.map(|answer| answer.trim().to_lowercase()),
+            add_word: request.add_word,
+        };
+        game.last_used = Instant::now();
+        let outcome = match game.session.submit_feedback(feedback) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.games.remove(&id);
+                return Response::error(500, e);
+            }
+        };
+        let events = game.take_events();
+        let Some(outcome) = outcome else {
+            return Response::ok(json!({
+                "game_id": id.to_string(),
+                "finished": false,
+                "guess": game.session.current_guess(),
+                "events": events,
+            }));
+        };
+
+        let results = game.session.get_session_results();
+        self.games.remove(&id);
+        Response::ok(json!({
+            "game_id": id.to_string(),
+            "finished": true,
+            "result": {
+                "outcome": outcome,
+                "win": results.win,
+                "guesses": results.number_of_guesses,
+                "word": results.word,
+            },
+            "events": events,
+        }))
+    }
+
+    fn suggestions(&self, query: &HashMap<String, String>) -> Response {
+        let history = query.get("history").map(String::as_str).unwrap_or("");
+        let history = match suggest::parse_history(&history.replace(',', " ")) {
+            Ok(history) => history,
+            Err(e) => return Response::error(400, e),
+        };
+        let limit = match query.get("limit").map(|limit| limit.parse::<usize>()) {
+            None => DEFAULT_CANDIDATE_LIMIT,
+            Some(Ok(limit)) => limit,
+            Some(Err(e)) => return Response::error(400, format!("Invalid limit: {e}")),
+        };
+
+        let result = match self.config.past_answer_mode {
+            PastAnswerMode::Ignore => Ok(Default::default()),
+            _ => self.result_db.get_past_answers(),
+        }
+        .map_err(FatalError::from)
+        .and_then(|past_answers| {
+            suggest::suggest(&history, self.word_db, self.config, past_answers)
+        });
+        match result {
+            Ok(suggestion) => Response::ok(suggestion.to_json(limit)),
+            Err(e) => Response::error(500, e),
+        }
+    }
+
+    /// Statistics for one profile, the active one unless `profile` is given, or a leaderboard of every profile.
+    fn stats(&self, query: &HashMap<String, String>, leaderboard: bool) -> Response {
+        let date = |key: &str| {
+            query
+                .get(key)
+                .map(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+                .transpose()
+                .map_err(|e| Response::error(400, format!("Invalid {key}: {e}")))
+        };
+        let (since, until) = match (date("since"), date("until")) {
+            (Ok(since), Ok(until)) => (since, until),
+            (Err(response), _) | (_, Err(response)) => return response,
+        };
+        let filter = SessionFilter {
+            session_type: query.get("session_type").cloned(),
+            profile: (!leaderboard).then(|| {
+                query
+                    .get("profile")
+                    .cloned()
+                    .unwrap_or_else(|| self.config.profile().to_string())
+            }),
+            ..SessionFilter::between(since, until)
+        };
+
+        let sessions = match self.result_db.get_session_results(&filter) {
+            Ok(sessions) => sessions,
+            Err(e) => return Response::error(500, e),
+        };
+        Response::ok(if leaderboard {
+            Leaderboard::new(filter, &sessions).to_json()
+        } else {
+            Report::new(filter, &sessions).to_json()
+        })
+    }
+}
+
+/// Splits a query string into decoded key and value pairs.
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(src: &str) -> String {
+    let bytes = src.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let byte = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Serves the solver over HTTP on `addr` until the process is stopped.
+/// Requests are handled one at a time, so games never run concurrently.
+pub fn serve(addr: &str, config: &Config, result_db: &DB, word_db: &DB) -> Result<(), ServerError> {
+    let server = tiny_http::Server::http(addr).map_err(|e| ServerError::Bind {
+        addr: addr.to_string(),
+        message: e.to_string(),
+    })?;
+    eprintln!("Listening on http://{addr}");
+
+    let mut solver = Solver::new(config, result_db, word_db);
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => {
+                let header = |name: &'static str| {
+                    request
+                        .headers()
+                        .iter()
+                        .find(|header| header.field.equiv(name))
+                        .map(|header| header.value.as_str())
+                };
+                solver.handle(&Request {
+                    method: request.method().as_str(),
+                    url: request.url(),
+                    origin: header("Origin"),
+                    content_type: header("Content-Type"),
+                    body: &body,
+                })
+            }
+            Err(e) => Response::error(400, e),
+        };
+        // A client that hung up before reading its reply does not affect anyone else.
+        let _ = request.respond(response.into_http());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionOutcome;
+    use crate::setup;
+
+    fn solver_dbs() -> (Config, DB, DB) {
+        let config = Config {
+            starting_word_limit: 1,
+            max_guesses: 2,
+            ..Config::default()
+        };
+        let word_db = setup::setup_word_db("crane\nslate\nplate\ngrate\nirate\n").unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        (config, result_db, word_db)
+    }
+
+    fn request<'r>(method: &'r str, url: &'r str, body: &'r str) -> Request<'r> {
+        Request {
+            method,
+            url,
+            content_type: (method == "POST").then_some("application/json"),
+            body,
+            ..Request::default()
+        }
+    }
+
+    fn call(solver: &mut Solver, method: &str, url: &str, body: &str) -> Response {
+        solver.handle(&request(method, url, body))
+    }
+
+    fn header<'r>(response: &'r Response, name: &str) -> Option<&'r str> {
+        response
+            .headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn start(solver: &mut Solver) -> (String, String) {
+        let response = call(solver, "POST", "/games", "");
+        assert_eq!(response.status, 200);
+        (
+            response.body["game_id"].as_str().unwrap().to_string(),
+            response.body["guess"].as_str().unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse_query("history=crane%3Annygy+slate:ngnny&limit=5&flag");
+        assert_eq!(query["history"], "crane:nnygy slate:ngnny");
+        assert_eq!(query["limit"], "5");
+        assert_eq!(query["flag"], "");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_game_over_http() {
+        let (config, result_db, word_db) = solver_dbs();
+        let mut solver = Solver::new(&config, &result_db, &word_db);
+        let (id, guess) = start(&mut solver);
+        let feedback = format!("/games/{id}/feedback");
+
+        assert_eq!(
+            call(&mut solver, "POST", &feedback, r#"{"feedback":"bad"}"#).status,
+            400
+        );
+        let state = call(&mut solver, "GET", &format!("/games/{id}"), "");
+        assert_eq!(state.body["guess"], guess.as_str());
+
+        let response = call(&mut solver, "POST", &feedback, r#"{"feedback":"GGGGG"}"#);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["finished"], true);
+        assert_eq!(response.body["result"]["win"], true);
+        assert_eq!(response.body["result"]["word"], guess.as_str());
+
+        assert_eq!(
+            call(&mut solver, "POST", &feedback, r#"{"feedback":"ggggg"}"#).status,
+            404
+        );
+        let stored = result_db
+            .get_session_results(&SessionFilter::default())
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].session_type, "Api");
+    }
+
+    #[test]
+    fn test_lost_game_takes_answer() {
+        let (mut config, result_db, word_db) = solver_dbs();
+        config.max_guesses = 1;
+        let mut solver = Solver::new(&config, &result_db, &word_db);
+        let (id, _) = start(&mut solver);
+        let feedback = format!("/games/{id}/feedback");
+
+        let response = call(
+            &mut solver,
+            "POST",
+            &feedback,
+            r#"{"feedback":"nnnnn","answer":"zzzzz"}"#,
+        );
+        assert_eq!(response.body["finished"], true);
+        assert_eq!(response.body["result"]["win"], false);
+        assert_eq!(response.body["result"]["word"], "zzzzz");
+        assert!(
+            response.body["events"]
+                .as_array()
+                .unwrap()
+                .contains(&json!({ "type": "out_of_guesses" }))
+        );
+    }
+
+    #[test]
+    fn test_abandon_game() {
+        let (config, result_db, word_db) = solver_dbs();
+        let mut solver = Solver::new(&config, &result_db, &word_db);
+        let (id, _) = start(&mut solver);
+
+        let path = format!("/games/{id}");
+        let response = call(&mut solver, "DELETE", &path, "");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["outcome"], "abandoned");
+        assert_eq!(call(&mut solver, "DELETE", &path, "").status, 404);
+        let stored = result_db
+            .get_session_results(&SessionFilter::default())
+            .unwrap();
+        assert_eq!(stored[0].outcome, SessionOutcome::Abandoned);
+        assert_eq!(
+            call(&mut solver, "GET", "/games/not-a-uuid", "").status,
+            404
+        );
+        assert_eq!(call(&mut solver, "GET", "/health", "").body["games"], 0);
+    }
+
+    #[test]
+    fn test_suggestions_and_stats() {
+        let (config, result_db, word_db) = solver_dbs();
+        let mut solver = Solver::new(&config, &result_db, &word_db);
+
+        let response = call(
+            &mut solver,
+            "GET",
+            "/suggestions?history=slate:nnggg&limit=5",
+            "",
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["remaining"], 2);
+        assert_eq!(response.body["candidates"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            call(&mut solver, "GET", "/suggestions?history=slate", "").status,
+            400
+        );
+
+        let (id, _) = start(&mut solver);
+        call(
+            &mut solver,
+            "POST",
+            &format!("/games/{id}/feedback"),
+            r#"{"feedback":"ggggg"}"#,
+        );
+        let stats = call(&mut solver, "GET", "/stats", "");
+        assert_eq!(stats.body["overall"]["games"], 1);
+        assert_eq!(stats.body["overall"]["win_rate"], 100.0);
+        assert_eq!(stats.body["by_session_type"]["Api"]["wins"], 1);
+        assert_eq!(
+            call(&mut solver, "GET", "/stats?since=yesterday", "").status,
+            400
+        );
+        let leaderboard = call(&mut solver, "GET", "/leaderboard", "");
+        assert_eq!(leaderboard.body["entries"][0]["profile"], "default");
+        assert_eq!(call(&mut solver, "PUT", "/stats", "").status, 405);
+        assert_eq!(call(&mut solver, "GET", "/nowhere", "").status, 404);
+    }
+
+    #[test]
+    fn test_post_requires_json() {
+        let (config, result_db, word_db) = solver_dbs();
+        let mut solver = Solver::new(&config, &result_db, &word_db);
+
+        for content_type in [
+            None,
+            Some("text/plain"),
+            Some("application/x-www-form-urlencoded"),
+        ] {
+            let response = solver.handle(&Request {
+                content_type,
+                ..request("POST", "/games", "")
+            });
+            assert_eq!(response.status, 415);
+        }
+        let response = solver.handle(&Request {
+            content_type: Some("application/json; charset=utf-8"),
+            ..request("POST", "/games", "")
+        });
+        assert_eq!(response.status, 200);
+        assert_eq!(call(&mut solver, "GET", "/health", "").body["games"], 1);
+    }
+
+    #[test]
+    fn test_cors_only_for_allowed_origins() {
+        let (mut config, result_db, word_db) = solver_dbs();
+        config.allowed_origins = vec!["http://localhost:3000".to_string()];
+        let mut solver = Solver::new(&config, &result_db, &word_db);
+
+        let preflight = solver.handle(&Request {
+            origin: Some("http://localhost:3000"),
+            ..request("OPTIONS", "/games/some-id", "")
+        });
+        assert_eq!(preflight.status, 204);
+        assert_eq!(
+            header(&preflight, "Access-Control-Allow-Origin"),
+            Some("http://localhost:3000")
+        );
+        assert_eq!(
+            header(&preflight, "Access-Control-Allow-Methods"),
+            Some(ALLOWED_METHODS)
+        );
+        assert_eq!(
+            header(&preflight, "Access-Control-Allow-Headers"),
+            Some("Content-Type")
+        );
+
+        let stats = solver.handle(&Request {
+            origin: Some("https://evil.example"),
+            ..request("GET", "/stats", "")
+        });
+        assert_eq!(stats.status, 200);
+        assert_eq!(header(&stats, "Access-Control-Allow-Origin"), None);
+        assert_eq!(call(&mut solver, "OPTIONS", "/nowhere", "").status, 404);
+    }
+}
@@ -1,20 +1,26 @@
-use crate::config::Config;
-use crate::constants::EXPECTED_FORMAT;
-use crate::error::FatalError;
-use crate::game_engine::GameEngine;
+use crate::cli::BUILTIN_SOURCE;
+use crate::config::{Config, PastAnswerMode};
+use crate::constants::{STRATEGY, WORD_LENGTH};
+use crate::error::{FatalError, RecoverableError};
+use crate::game_engine::{GameEngine, score_guess};
 use crate::input::InputSource;
 use crate::output::OutputSink;
-use crate::{DB, logs};
-use colored::Colorize;
+use crate::replay::{REPLAY_VERSION, ReplayError, ReplayFile, ReplayTurn};
+use crate::word_analyzer::Word;
+use crate::{DB, setup};
 use std::fmt::Display;
+use std::path::PathBuf;
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 #[derive(Debug, PartialEq)]
 pub enum SessionType {
     Interactive,
     Test,
     Api,
+    Archive,
 }
 
 impl SessionType {
@@ -24,6 +30,7 @@ impl SessionType {
             SessionType::Interactive => "Interactive",
             SessionType::Test => "Test",
             SessionType::Api => "Api",
+            SessionType::Archive => "Archive",
         }
     }
 }
@@ -34,6 +41,72 @@ impl Display for SessionType {
     }
 }
 
+/// How a finished game ended. Errors that stop a session are returned as `FatalError` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOutcome {
+    Won,
+    /// Every guess was used without finding the answer.
+    Lost,
+    /// No word in the list fit the feedback.
+    Stumped,
+    /// The player left before the game ended. Interactive games can still be resumed.
+    Abandoned,
+}
+
+impl SessionOutcome {
+    /// Converts the outcome to the string stored in the `status` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionOutcome::Won => "won",
+            SessionOutcome::Lost => "lost",
+            SessionOutcome::Stumped => "stumped",
+            SessionOutcome::Abandoned => "abandoned",
+        }
+    }
+}
+
+impl std::str::FromStr for SessionOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "won" => Ok(SessionOutcome::Won),
+            "lost" => Ok(SessionOutcome::Lost),
+            "stumped" => Ok(SessionOutcome::Stumped),
+            "abandoned" => Ok(SessionOutcome::Abandoned),
+            other => Err(format!("Unknown session outcome '{other}'")),
+        }
+    }
+}
+
+impl Display for SessionOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// One turn of a session: the word that was guessed and the `gyngy` feedback it received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuessRecord {
+    pub guess: String,
+    pub feedback: String,
+}
+
+/// Saved state of an unfinished session, written after every turn so the session can be resumed later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveSession {
+    pub session_id: Uuid,
+    pub start_date: chrono::DateTime<chrono::Utc>,
+    pub updated_date: chrono::DateTime<chrono::Utc>,
+    pub session_type: String,
+    /// The guess that was suggested last and is still waiting for feedback.
+    pub current_guess: String,
+    pub strategy: String,
+    pub seed: Option<u64>,
+    pub profile: String,
+}
+
 pub struct SessionResults {
     pub session_id: Uuid,
     pub start_date: chrono::DateTime<chrono::Utc>,
@@ -42,6 +115,79 @@ pub struct SessionResults {
     pub word: String,
     pub number_of_guesses: u8,
     pub win: bool,
+    pub profile: String,
+    pub outcome: SessionOutcome,
+    /// Seed of the session's random choices. Missing for sessions stored before seeds were recorded.
+    pub seed: Option<u64>,
+}
+
+/// How a turn ends once its feedback has been applied.
+#[derive(Debug)]
+pub enum Turn {
+    Won,
+    /// The last guess was used, with the number of candidates that were left.
+    Lost(usize),
+    /// No candidate is left to guess.
+    Stumped(Vec<Word>, RecoverableError),
+    /// The next guess, which is now the engine's current guess, and the candidates it was picked from.
+    Next(Vec<Word>, String),
+}
+
+impl Turn {
+    /// The outcome of the game, or `None` when it goes on.
+    pub fn outcome(&self) -> Option<SessionOutcome> {
+        match self {
+            Turn::Won => Some(SessionOutcome::Won),
+            Turn::Lost(_) => Some(SessionOutcome::Lost),
+            Turn::Stumped(..) => Some(SessionOutcome::Stumped),
+            Turn::Next(..) => None,
+        }
+    }
+}
+
+/// Applies the feedback for guess number `guesses` to `engine` and decides how the turn ends. A win is checked
+/// first, then running out of guesses, then running out of candidates. Sessions and benchmarks both play turns with it.
+pub fn play_turn(
+    engine: &mut GameEngine,
+    word_db: &DB,
+    feedback: &str,
+    guesses: u8,
+    max_guesses: u8,
+) -> Result<Turn, rusqlite::Error> {
+    engine.parse_input(feedback);
+    if engine.check_for_win() {
+        return Ok(Turn::Won);
+    }
+    let candidates = engine.rank_candidates(word_db.filter_words(&engine.get_pattern())?);
+    if guesses >= max_guesses {
+        return Ok(Turn::Lost(candidates.len()));
+    }
+    Ok(match engine.choose_guess(&candidates) {
+        Ok(guess) => Turn::Next(candidates, guess),
+        Err(e) => Turn::Stumped(candidates, e),
+    })
+}
+
+/// Largest seed that is drawn, so every seed fits in a TOML integer and can be copied into `config.toml`.
+pub const MAX_SEED: u64 = i64::MAX as u64;
+
+/// Draws a new seed from `rng`, at most `MAX_SEED`.
+pub fn draw_seed(rng: &mut impl Rng) -> u64 {
+    rng.gen_range(0..=MAX_SEED)
+}
+
+/// Picks the opener from the `limit` most probable words with `rng`, so the same seed always opens with the same word.
+/// Fails with `QueryReturnedNoRows` when the word list is empty or `limit` is 0.
+pub fn pick_starting_word(
+    word_db: &DB,
+    limit: u8,
+    rng: &mut impl Rng,
+) -> Result<String, rusqlite::Error> {
+    let words = word_db.get_top_words(limit)?;
+    if words.is_empty() {
+        return Err(rusqlite::Error::QueryReturnedNoRows);
+    }
+    Ok(words[rng.gen_range(0..words.len())].as_str())
 }
 
 // The session module is the orchestrator of each game, getting the input type, creating the game engine and managing the game state.
@@ -55,7 +201,18 @@ pub struct Session<'c, 'a, I: InputSource, O: OutputSink> {
     input_source: I,
     output_sink: O,
     words_guessed: Vec<String>,
+    feedback_history: Vec<String>,
     number_of_guesses: u8,
+    answer: Option<String>,
+    /// Set once the game has ended.
+    outcome: Option<SessionOutcome>,
+    /// Turns played by this process with the engine state they led to, for replay files.
+    recorded_turns: Vec<ReplayTurn>,
+    /// Where replay files are written when the solver is stumped. `None` turns capture off.
+    replay_dir: Option<PathBuf>,
+    /// Seed of `rng`, stored with the results so the session can be reproduced.
+    seed: u64,
+    rng: StdRng,
     config: &'c Config,
 }
 impl<'c, 'a, I: InputSource, O: OutputSink> Display for Session<'c, 'a, I, O> {
@@ -85,6 +242,9 @@ impl<'c, 'a, I: InputSource, O: OutputSink> Session<'c, 'a, I, O> {
         result_db: &'a DB,
         in_memory_db: &'a DB,
     ) -> Self {
+        let seed = config
+            .seed
+            .unwrap_or_else(|| draw_seed(&mut rand::thread_rng()));
         Session {
             session_id: Uuid::new_v4(),
             session_type,
@@ -95,123 +255,780 @@ impl<'c, 'a, I: InputSource, O: OutputSink> Session<'c, 'a, I, O> {
             input_source: input,
             output_sink: output,
             words_guessed: Vec::new(),
+            feedback_history: Vec::new(),
             number_of_guesses: 0,
+            answer: None,
+            outcome: None,
+            recorded_turns: Vec::new(),
+            replay_dir: None,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
             config,
         }
     }
 
+    /// Replaces the seed of the session's random choices. Call it before `initialize`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Writes a replay file to `dir` whenever the solver is stumped.
+    pub fn capture_replays(&mut self, dir: PathBuf) {
+        self.replay_dir = Some(dir);
+    }
+
+    pub fn session_id(&self) -> Uuid {
+        self.session_id
+    }
+
+    /// True once the real answer has been found or given after a lost game.
+    pub fn is_answer_known(&self) -> bool {
+        self.game_engine.check_for_win() || self.answer.is_some()
+    }
+
+    /// The guess waiting for feedback.
+    pub fn current_guess(&self) -> &str {
+        self.game_engine.get_current_guess()
+    }
+
     //IMPORTANT: we need to make sure the main function handles the errors propagated from here
 
     /// Starts the game session, initializes the game engine with the starting word .
     pub fn initialize(&mut self) -> Result<(), FatalError> {
-        let words = self
-            .in_memory_db
-            .get_top_words(self.config.get_limit())
-            .map_err(FatalError::DatabaseError)?;
-        let rng = rand::thread_rng().gen_range(0..words.len());
-
-        let starting_word = words[rng].clone().as_str();
+        let starting_word =
+            pick_starting_word(self.in_memory_db, self.config.get_limit(), &mut self.rng)
+                .map_err(FatalError::DatabaseError)?;
         self.words_guessed.push(starting_word.clone());
         self.game_engine.set_starting_word(starting_word);
+        self.load_past_answers()?;
+        log::info!(
+            "session={} start type={} profile={} seed={} guess={}",
+            self.session_id,
+            self.session_type,
+            self.config.profile(),
+            self.seed,
+            self.game_engine.get_current_guess()
+        );
+
+        self.save_progress()
+    }
 
+    /// Restores a saved unfinished session instead of calling `initialize`. The game engine is rebuilt by replaying the stored feedback history.
+    pub fn resume(
+        &mut self,
+        active: &ActiveSession,
+        history: &[GuessRecord],
+    ) -> Result<(), FatalError> {
+        self.session_id = active.session_id;
+        self.start_date = active.start_date;
+        if let Some(seed) = active.seed {
+            self.set_seed(seed);
+        }
+        self.game_engine = GameEngine::from_history(
+            history
+                .iter()
+                .map(|record| (record.guess.as_str(), record.feedback.as_str())),
+        );
+        self.game_engine
+            .set_starting_word(active.current_guess.clone());
+
+        self.words_guessed = history.iter().map(|r| r.guess.clone()).collect();
+        self.words_guessed.push(active.current_guess.clone());
+        self.feedback_history = history.iter().map(|r| r.feedback.clone()).collect();
+        self.number_of_guesses = history.len() as u8;
+        log::info!(
+            "session={} resume guesses={} guess={}",
+            self.session_id,
+            self.number_of_guesses,
+            active.current_guess
+        );
+        self.load_past_answers()
+    }
+
+    /// Hands the stored past answers to the game engine when the config asks for them to be dropped or down-weighted.
+    fn load_past_answers(&mut self) -> Result<(), FatalError> {
+        if self.config.past_answer_mode == PastAnswerMode::Ignore {
+            return Ok(());
+        }
+        let past_answers = self.result_db.get_past_answers()?;
+        self.game_engine.set_past_answers(
+            past_answers,
+            self.config.past_answer_mode,
+            self.config.past_answer_weight,
+        );
         Ok(())
     }
 
-    pub fn start_interactive(&mut self) -> Result<(), FatalError> {
-        // welcome the user
-        welcome();
-        self.output_sink
-            .output_guess(self.game_engine.get_current_guess())?;
-        self.run_game_loop()
+    /// Announces the game, outputs the current guess and plays until the game ends.
+    /// Errors other than failed input or output are also reported to the output sink.
+    pub fn start(&mut self) -> Result<SessionOutcome, FatalError> {
+        let result = self.play();
+        if let Err(e) = &result
+            && !matches!(e, FatalError::IOError(_))
+        {
+            self.output_sink.fatal_error(&e.to_string())?;
+        }
+        result
     }
 
-    pub fn start_test_session(&mut self) -> Result<(), FatalError> {
+    fn play(&mut self) -> Result<SessionOutcome, FatalError> {
+        self.output_sink.game_start()?;
         self.output_sink
             .output_guess(self.game_engine.get_current_guess())?;
         self.run_game_loop()
     }
 
-    pub fn run_game_loop(&mut self) -> Result<(), FatalError> {
+    pub fn run_game_loop(&mut self) -> Result<SessionOutcome, FatalError> {
         loop {
             // Get user feedback on the last guess
-            let user_input = self.input_source.get_feedback()?;
-            self.number_of_guesses += 1;
-
-            // process feedback and check game state
-            self.game_engine.parse_input(&user_input);
-            if self.game_engine.check_for_win() || self.out_of_guesses() {
-                if self.out_of_guesses() && self.session_type == SessionType::Interactive {
-                    println!("Out of guesses!");
-                }
-                return self.store_session_results();
+            let Some(user_input) = self.input_source.get_feedback()? else {
+                return self.abandon();
+            };
+            if let Some(outcome) = self.submit_feedback(user_input)? {
+                return Ok(outcome);
             }
+        }
+    }
+
+    /// Plays one turn with feedback for the current guess: either the next guess is output or the game ends and is stored.
+    /// Returns the outcome once the game is over. Callers that receive feedback themselves use this instead of `run_game_loop`.
+    pub fn submit_feedback(
+        &mut self,
+        user_input: String,
+    ) -> Result<Option<SessionOutcome>, FatalError> {
+        self.number_of_guesses += 1;
+        self.feedback_history.push(user_input.clone());
+
+        let guess = self.game_engine.get_current_guess().to_string();
+        let turn = play_turn(
+            &mut self.game_engine,
+            self.in_memory_db,
+            &user_input,
+            self.number_of_guesses,
+            self.config.get_max_guesses(),
+        )?;
+        let next_guess = match turn {
+            Turn::Won => {
+                self.record_turn(guess, user_input, 1);
+                self.output_sink.won(self.number_of_guesses)?;
+                return self.finish(SessionOutcome::Won).map(Some);
+            }
+            Turn::Lost(candidates) => {
+                self.record_turn(guess, user_input, candidates);
+                self.output_sink.out_of_guesses()?;
+                self.ask_for_answer()?;
+                return self.finish(SessionOutcome::Lost).map(Some);
+            }
+            Turn::Stumped(candidates, e) => {
+                self.record_turn(guess, user_input, candidates.len());
+                self.output_sink.candidates(&candidates)?;
+                log::warn!("session={} stumped: {e}", self.session_id);
+                self.output_sink.stumped(&e.to_string())?;
 
-            // Determine the next guess
-            let pattern = self.game_engine.get_pattern();
-            let possible_words = self.in_memory_db.filter_words(&pattern)?;
+                self.ask_for_answer()?;
+                self.save_replay(&e.to_string());
+                return self.finish(SessionOutcome::Stumped).map(Some);
+            }
+            Turn::Next(candidates, next_guess) => {
+                self.record_turn(guess, user_input, candidates.len());
+                self.output_sink.candidates(&candidates)?;
+                next_guess
+            }
+        };
+
+        // Output the next guess
+        self.words_guessed.push(next_guess.clone());
+        self.save_progress()?;
+        self.output_sink.output_guess(&next_guess)?;
+        Ok(None)
+    }
 
-            let next_guess = match self.game_engine.get_next_guess(possible_words) {
-                Ok(guess) => guess,
-                Err(e) => {
-                    println!("I am stumped! {e}");
-                    let session_state = format!("{self}");
-                    logs::log_session_state(session_state)?;
+    /// Ends the game without an answer and records it as abandoned.
+    /// The saved progress of an interactive game is kept, so it can still be resumed and its record replaced.
+    pub fn abandon(&mut self) -> Result<SessionOutcome, FatalError> {
+        self.finish(SessionOutcome::Abandoned)
+    }
 
-                    return self.store_session_results();
+    /// Logs the feedback for a guess and how many candidates are left after it, and keeps it for replay files.
+    fn record_turn(&mut self, guess: String, feedback: String, candidates: usize) {
+        log::info!(
+            "session={} turn={} guess={guess} feedback={feedback} candidates={candidates}",
+            self.session_id,
+            self.number_of_guesses
+        );
+        self.recorded_turns.push(ReplayTurn {
+            guess,
+            feedback,
+            pattern: Some(self.game_engine.get_pattern()),
+            candidates: Some(candidates),
+        });
+    }
+
+    /// Builds a replay file of the game so far. Turns played before a resume have no recorded engine state.
+    pub fn replay_file(&self, reason: &str) -> Result<ReplayFile, ReplayError> {
+        let history = self.guess_history();
+        let resumed_turns = history.len().saturating_sub(self.recorded_turns.len());
+        let turns = history
+            .into_iter()
+            .take(resumed_turns)
+            .map(|record| ReplayTurn {
+                guess: record.guess,
+                feedback: record.feedback,
+                pattern: None,
+                candidates: None,
+            })
+            .chain(self.recorded_turns.iter().cloned())
+            .collect();
+        let past_answers = match self.config.past_answer_mode {
+            PastAnswerMode::Ignore => Vec::new(),
+            _ => {
+                let mut past_answers: Vec<String> =
+                    self.result_db.get_past_answers()?.into_iter().collect();
+                past_answers.sort();
+                past_answers
+            }
+        };
+
+        Ok(ReplayFile {
+            version: REPLAY_VERSION,
+            crackle_version: env!("CARGO_PKG_VERSION").to_string(),
+            session_id: self.session_id.to_string(),
+            created: chrono::Utc::now().to_rfc3339(),
+            reason: reason.to_string(),
+            strategy: STRATEGY.to_string(),
+            word_source: self
+                .config
+                .active_word_source
+                .clone()
+                .unwrap_or_else(|| BUILTIN_SOURCE.to_string()),
+            word_source_hash: setup::active_word_source_hash(self.config, self.result_db)?,
+            seed: Some(self.seed),
+            past_answer_mode: self.config.past_answer_mode,
+            past_answer_weight: self.config.past_answer_weight,
+            past_answers,
+            secret: self.answer.clone(),
+            turns,
+        })
+    }
+
+    /// Writes a replay file when capture is on. A replay that cannot be written is logged and does not end the game.
+    fn save_replay(&self, reason: &str) {
+        let Some(dir) = &self.replay_dir else {
+            return;
+        };
+        match self.replay_file(reason).and_then(|replay| replay.save(dir)) {
+            Ok(path) => log::warn!(
+                "session={} replay written to {}",
+                self.session_id,
+                path.display()
+            ),
+            Err(e) => log::error!("session={} could not write replay: {e}", self.session_id),
+        }
+    }
+
+    fn finish(&mut self, outcome: SessionOutcome) -> Result<SessionOutcome, FatalError> {
+        log::info!(
+            "session={} end outcome={outcome} guesses={}",
+            self.session_id,
+            self.number_of_guesses
+        );
+        self.outcome = Some(outcome);
+        self.store_session_results()?;
+        Ok(outcome)
+    }
+
+    /// Asks the input source for the real answer after a lost or stumped game and keeps it if it agrees with every piece of feedback.
+    /// Answers missing from the word list can be added to the custom word list so the solver can find them next time.
+    fn ask_for_answer(&mut self) -> Result<(), FatalError> {
+        loop {
+            let Some(answer) = self.input_source.get_answer()? else {
+                return Ok(());
+            };
+            match self.check_answer(&answer) {
+                Ok(()) => {
+                    self.add_missing_answer(&answer)?;
+                    self.answer = Some(answer);
+                    return Ok(());
                 }
+                Err(e) => self.output_sink.recoverable_error(&e.to_string())?,
+            }
+        }
+    }
+
+    /// Checks that the answer has the right shape and would have produced the feedback recorded for every guess.
+    fn check_answer(&self, answer: &str) -> Result<(), RecoverableError> {
+        if answer.len() != WORD_LENGTH {
+            return Err(RecoverableError::InvalidWordLength(answer.len()));
+        }
+        if !answer.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(RecoverableError::InputError(format!(
+                "'{answer}' must only contain letters"
+            )));
+        }
+        for record in self.guess_history() {
+            let Some(expected) = score_guess(&record.guess, answer) else {
+                return Err(RecoverableError::InvalidWordLength(record.guess.len()));
             };
+            if expected != record.feedback {
+                return Err(RecoverableError::AnswerMismatch {
+                    answer: answer.to_string(),
+                    guess: record.guess,
+                    feedback: record.feedback,
+                    expected,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn add_missing_answer(&mut self, answer: &str) -> Result<(), FatalError> {
+        if self.in_memory_db.contains_word(answer)?
+            || !self.input_source.confirm_add_word(answer)?
+        {
+            return Ok(());
+        }
+        setup::add_custom_word(&self.config.custom_words_path(), answer)?;
+        let word =
+            Word::new(0, 0.0, answer).map_err(|e| FatalError::WordAnalyzer(e.to_string()))?;
+        self.in_memory_db.add_word(word)?;
+        Ok(())
+    }
 
-            // Output the next guess
-            self.words_guessed.push(next_guess.clone());
-            self.output_sink.output_guess(&next_guess)?;
+    /// Saves the guesses so far and the pending guess so an interactive session can be resumed if it is interrupted.
+    fn save_progress(&self) -> Result<(), FatalError> {
+        if self.session_type != SessionType::Interactive {
+            return Ok(());
         }
+        let active = ActiveSession {
+            session_id: self.session_id,
+            start_date: self.start_date,
+            updated_date: chrono::Utc::now(),
+            session_type: self.session_type.as_str().to_string(),
+            current_guess: self.game_engine.get_current_guess().to_string(),
+            strategy: STRATEGY.to_string(),
+            seed: Some(self.seed),
+            profile: self.config.profile().to_string(),
+        };
+        self.result_db.save_active_session(&active)?;
+        self.result_db
+            .store_guesses(&self.session_id, &self.guess_history())?;
+        Ok(())
     }
-    pub fn store_session_results(&self) -> Result<(), FatalError> {
+    pub fn store_session_results(&mut self) -> Result<(), FatalError> {
         let session_results = self.get_session_results();
         // Store game_results in database or file
         self.result_db
             .store_session_results(&session_results)
             .map_err(FatalError::DatabaseError)?;
-        println!("Game results stored successfully!");
-        println!("See you tomorrow!");
+        self.result_db
+            .store_guesses(&self.session_id, &self.guess_history())
+            .map_err(FatalError::DatabaseError)?;
+        if session_results.outcome != SessionOutcome::Abandoned {
+            self.result_db
+                .delete_active_session(&self.session_id)
+                .map_err(FatalError::DatabaseError)?;
+        }
+        self.output_sink.stored(&session_results)?;
         Ok(())
     }
 
-    fn out_of_guesses(&self) -> bool {
-        self.number_of_guesses >= self.config.get_max_guesses()
+    /// Pairs every guess with the feedback it received. A guess still waiting for feedback is left out.
+    pub fn guess_history(&self) -> Vec<GuessRecord> {
+        self.words_guessed
+            .iter()
+            .zip(&self.feedback_history)
+            .map(|(guess, feedback)| GuessRecord {
+                guess: guess.clone(),
+                feedback: feedback.clone(),
+            })
+            .collect()
     }
 
-    fn get_session_results(&self) -> SessionResults {
+    /// The results of the session as they are stored when it ends.
+    pub fn get_session_results(&self) -> SessionResults {
         SessionResults {
             session_id: self.session_id,
             start_date: self.start_date,
             end_date: chrono::Utc::now(),
             session_type: self.session_type.as_str().to_string(),
-            word: self.game_engine.get_pattern(),
+            word: self
+                .answer
+                .clone()
+                .unwrap_or_else(|| self.game_engine.get_pattern()),
             number_of_guesses: self.number_of_guesses,
             win: self.game_engine.check_for_win(),
+            profile: self.config.profile().to_string(),
+            // A game that has not ended yet would be stored as abandoned.
+            outcome: self.outcome.unwrap_or(SessionOutcome::Abandoned),
+            seed: Some(self.seed),
         }
     }
 }
 
-fn welcome() {
-    println!("Welcome to Crackle!\r\n");
-    println!("I will give you a word to try based on positional frequency");
-    println!(
-        "To achieve this, you will need to enter {}, {}, and {}",
-        "G for green".green(),
-        "Y for yellow".yellow(),
-        "N for gray".bright_black()
-    );
-    println!("Example: {EXPECTED_FORMAT}");
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::{InteractiveInput, TestInput};
+    use crate::output::{InteractiveOutput, TestOutput};
+    use crate::shared_state::SharedTestState;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const WORDS: &str = "crane\nslate\nplate\ngrate\nirate\n";
+
+    fn test_config() -> Config {
+        Config {
+            starting_word_limit: 1,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_interrupted_session_can_be_resumed() {
+        let config = test_config();
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+
+        let input = InteractiveInput::new("nnggg\nexit\n".as_bytes());
+        let mut session = Session::new(
+            SessionType::Interactive,
+            input,
+            InteractiveOutput::new(Vec::new()),
+            &config,
+            &result_db,
+            &word_db,
+        );
+        session.initialize().unwrap();
+        let first_guess = session.game_engine.get_current_guess().to_string();
+        assert_eq!(session.run_game_loop().unwrap(), SessionOutcome::Abandoned);
+        let pending_guess = session.game_engine.get_current_guess().to_string();
+
+        let saved = result_db
+            .get_active_sessions(
+                session.start_date - chrono::Duration::seconds(1),
+                config.profile(),
+            )
+            .unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].current_guess, pending_guess);
+        let history = result_db.get_guesses(&saved[0].session_id).unwrap();
+        assert_eq!(
+            history,
+            vec![GuessRecord {
+                guess: first_guess,
+                feedback: "nnggg".to_string()
+            }]
+        );
+
+        let input = InteractiveInput::new("ggggg\n".as_bytes());
+        let mut resumed = Session::new(
+            SessionType::Interactive,
+            input,
+            InteractiveOutput::new(Vec::new()),
+            &config,
+            &result_db,
+            &word_db,
+        );
+        resumed.resume(&saved[0], &history).unwrap();
+        assert_eq!(resumed.session_id, saved[0].session_id);
+        assert_eq!(resumed.seed(), session.seed());
+        assert_eq!(resumed.number_of_guesses, 1);
+        assert_eq!(
+            result_db
+                .get_session_results(&crate::database::SessionFilter::default())
+                .unwrap()[0]
+                .outcome,
+            SessionOutcome::Abandoned
+        );
+        assert_eq!(resumed.run_game_loop().unwrap(), SessionOutcome::Won);
+
+        let results = result_db
+            .get_session_results(&crate::database::SessionFilter::default())
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].number_of_guesses, 2);
+        assert!(results[0].win);
+        assert_eq!(results[0].outcome, SessionOutcome::Won);
+        assert!(
+            result_db
+                .get_active_sessions(
+                    session.start_date - chrono::Duration::seconds(1),
+                    config.profile()
+                )
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn interactive_session<'a>(
+        config: &'a Config,
+        result_db: &'a DB,
+        word_db: &'a DB,
+        input: &'static str,
+    ) -> Session<'a, 'a, InteractiveInput<&'static [u8]>, InteractiveOutput<Vec<u8>>> {
+        Session::new(
+            SessionType::Interactive,
+            InteractiveInput::new(input.as_bytes()),
+            InteractiveOutput::new(Vec::new()),
+            config,
+            result_db,
+            word_db,
+        )
+    }
+
+    #[test]
+    fn test_seed_picks_the_same_opener() {
+        let config = Config {
+            starting_word_limit: 5,
+            seed: Some(11),
+            ..Config::default()
+        };
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        let openers: Vec<String> = (0..2)
+            .map(|_| {
+                let mut session = interactive_session(&config, &result_db, &word_db, "");
+                session.initialize().unwrap();
+                assert_eq!(session.get_session_results().seed, Some(11));
+                session.current_guess().to_string()
+            })
+            .collect();
+        assert_eq!(openers[0], openers[1]);
+
+        let mut session = interactive_session(&config, &result_db, &word_db, "");
+        session.set_seed(12);
+        assert_eq!(session.seed(), 12);
+        assert_eq!(session.replay_file("test").unwrap().seed, Some(12));
+    }
+
+    #[test]
+    fn test_play_turn_checks_win_before_running_out() {
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let mut engine = GameEngine::new();
+        engine.set_starting_word("slate".to_string());
+        assert!(matches!(
+            play_turn(&mut engine, &word_db, "ggggg", 6, 6).unwrap(),
+            Turn::Won
+        ));
+
+        let mut engine = GameEngine::new();
+        engine.set_starting_word("slate".to_string());
+        assert!(matches!(
+            play_turn(&mut engine, &word_db, "nnggg", 6, 6).unwrap(),
+            Turn::Lost(candidates) if candidates > 0
+        ));
+
+        let mut engine = GameEngine::new();
+        engine.set_starting_word("slate".to_string());
+        let Turn::Next(candidates, guess) =
+            play_turn(&mut engine, &word_db, "nnggg", 1, 6).unwrap()
+        else {
+            panic!("expected a next guess");
+        };
+        assert!(candidates.iter().any(|word| word.as_str() == guess));
+        assert_eq!(engine.get_current_guess(), guess);
+    }
+
+    #[test]
+    fn test_no_opener_without_words() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        assert!(pick_starting_word(&word_db, 0, &mut rng).is_err());
+        let empty = DB::new_in_memory().unwrap();
+        assert!(pick_starting_word(&empty, 5, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_drawn_seeds_fit_in_config() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut config = Config::default();
+        for _ in 0..100 {
+            let seed = draw_seed(&mut rng);
+            config.set_value("seed", Some(&seed.to_string())).unwrap();
+            assert_eq!(config.seed, Some(seed));
+        }
+        config.seed = Some(MAX_SEED);
+        let saved: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(saved.seed, Some(MAX_SEED));
+    }
+
+    #[test]
+    fn test_check_answer_against_feedback() {
+        let config = test_config();
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        let mut session = interactive_session(&config, &result_db, &word_db, "");
+        session.words_guessed = vec!["crane".to_string()];
+        session.feedback_history = vec!["nnggg".to_string()];
+
+        assert!(session.check_answer("plane").is_ok());
+        assert!(matches!(
+            session.check_answer("slate"),
+            Err(RecoverableError::AnswerMismatch { .. })
+        ));
+        assert!(session.check_answer("plan").is_err());
+        assert!(session.check_answer("pl4ne").is_err());
+    }
+
+    #[test]
+    fn test_lost_game_records_answer_and_custom_word() {
+        let config = Config {
+            max_guesses: 1,
+            app_db: std::env::temp_dir()
+                .join(format!("crackle-test-{}", Uuid::new_v4()))
+                .join("crackle.db"),
+            ..test_config()
+        };
+        std::fs::create_dir_all(config.app_db.parent().unwrap()).unwrap();
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+
+        // "slate" contradicts the feedback for "crane", and "plane" is not in the word list.
+        let mut session =
+            interactive_session(&config, &result_db, &word_db, "nnggg\nslate\nplane\ny\n");
+        session.words_guessed.push("crane".to_string());
+        session.game_engine.set_starting_word("crane".to_string());
+        session.run_game_loop().unwrap();
+
+        let results = result_db
+            .get_session_results(&crate::database::SessionFilter::default())
+            .unwrap();
+        assert_eq!(results[0].word, "plane");
+        assert!(!results[0].win);
+        assert!(word_db.contains_word("plane").unwrap());
+        let custom = setup::load_custom_words(&config.custom_words_path()).unwrap();
+        assert_eq!(custom, "plane\n");
+
+        std::fs::remove_dir_all(config.app_db.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_stumped_game_writes_replay() {
+        let config = test_config();
+        let dir = std::env::temp_dir().join(format!("crackle-replays-{}", Uuid::new_v4()));
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+
+        let mut session = interactive_session(&config, &result_db, &word_db, "ngggg\n\n");
+        session.capture_replays(dir.clone());
+        session.words_guessed.push("crane".to_string());
+        session.game_engine.set_starting_word("crane".to_string());
+        assert_eq!(session.run_game_loop().unwrap(), SessionOutcome::Stumped);
+
+        let path = dir.join(format!("{}.json", session.session_id));
+        let file = ReplayFile::load(&path).unwrap();
+        assert_eq!(file.reason, "No guess found");
+        assert_eq!(file.secret, None);
+        assert_eq!(file.turns[0].pattern.as_deref(), Some("_rane"));
+        let report = crate::replay::replay(&file, &word_db, &file.word_source_hash).unwrap();
+        assert!(report.first_divergence().is_none());
+        assert!(report.stumped);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_test_session_stores_secret_on_loss() {
+        let config = Config {
+            max_guesses: 1,
+            ..test_config()
+        };
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        let shared_state = Rc::new(RefCell::new(SharedTestState::new()));
+        let mut session = Session::new(
+            SessionType::Test,
+            TestInput::new("irate".to_string(), Rc::clone(&shared_state)),
+            TestOutput::new(Rc::clone(&shared_state)),
+            &config,
+            &result_db,
+            &word_db,
+        );
+        session.initialize().unwrap();
+        session.start().unwrap();
+
+        let results = result_db
+            .get_session_results(&crate::database::SessionFilter::default())
+            .unwrap();
+        assert_eq!(results[0].word, "irate");
+    }
+
+    /// Records the name of every output event in order.
+    struct RecordingOutput(Rc<RefCell<Vec<String>>>);
+
+    impl OutputSink for RecordingOutput {
+        fn game_start(&mut self) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("game_start".to_string());
+            Ok(())
+        }
+        fn output_guess(&mut self, _guess: &str) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("guess".to_string());
+            Ok(())
+        }
+        fn candidates(&mut self, candidates: &[Word]) -> Result<(), std::io::Error> {
+            self.0
+                .borrow_mut()
+                .push(format!("candidates {}", candidates.len()));
+            Ok(())
+        }
+        fn won(&mut self, guesses: u8) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push(format!("won {guesses}"));
+            Ok(())
+        }
+        fn out_of_guesses(&mut self) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("out_of_guesses".to_string());
+            Ok(())
+        }
+        fn stumped(&mut self, _reason: &str) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("stumped".to_string());
+            Ok(())
+        }
+        fn stored(&mut self, _results: &SessionResults) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("stored".to_string());
+            Ok(())
+        }
+        fn fatal_error(&mut self, _msg: &str) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("fatal_error".to_string());
+            Ok(())
+        }
+        fn recoverable_error(&mut self, _msg: &str) -> Result<(), std::io::Error> {
+            self.0.borrow_mut().push("recoverable_error".to_string());
+            Ok(())
+        }
+    }
+
+    fn record_events(input: &str) -> Vec<String> {
+        let config = test_config();
+        let word_db = setup::setup_word_db(WORDS).unwrap();
+        let result_db = DB::new_results_in_memory().unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut session = Session::new(
+            SessionType::Interactive,
+            InteractiveInput::new(input.as_bytes()),
+            RecordingOutput(Rc::clone(&events)),
+            &config,
+            &result_db,
+            &word_db,
+        );
+        session.initialize().unwrap();
+        session.start().unwrap();
+        events.take()
+    }
 
     #[test]
-    fn test_welcome() {
-        welcome();
+    fn test_session_reports_through_output_sink() {
+        assert_eq!(
+            record_events("ggggg\n"),
+            vec!["game_start", "guess", "won 1", "stored"]
+        );
+        assert_eq!(
+            record_events("yyyyy\n\n"),
+            vec!["game_start", "guess", "candidates 0", "stumped", "stored"]
+        );
     }
 }
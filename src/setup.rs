@@ -1,5 +1,11 @@
-use crate::database::DB;
-use crate::word_analyzer::WordAnalyzer;
+use crate::cli::BUILTIN_SOURCE;
+use crate::config::Config;
+use crate::constants::WORD_LIST;
+use crate::database::{DB, WordSource};
+use crate::word_analyzer::{ANALYZER_VERSION, Word, WordAnalyzer};
+use crate::word_list::{ValidationReport, validate_word_list};
+use sha2::{Digest, Sha256};
+use std::path::Path;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,10 +17,21 @@ pub enum SetupError {
 
     #[error("Word source does not exist")]
     WordSourceDoesNotExist,
+    #[error("No word source named '{0}' is registered")]
+    UnknownWordSource(String),
+    #[error("Word source '{0}' does not contain any valid words")]
+    EmptyWordSource(String),
+    #[error("'{0}' cannot name a word source, names must not be empty or '{BUILTIN_SOURCE}'")]
+    InvalidSourceName(String),
     #[error("Can not get working directory")]
     WorkingDirectoryError,
 }
 
+/// Words from the embedded list with the probabilities calculated by `build.rs`.
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded_words.rs"));
+}
+
 // We need to set up the database, instantiate the WordParser, and then start parsing the words.txt in the root directory and adding the finished words to the database.
 
 /// The setup function gets the path to the initial word source file, opens the file and reads each word from the file calculates the probability and then adds it to the database.
@@ -30,6 +47,63 @@ pub fn setup_word_db(word_src: &str) -> Result<DB, SetupError> {
     Ok(word_db)
 }
 
+/// Reads the custom word list, returning an empty list if it has not been created yet.
+pub fn load_custom_words(path: &std::path::Path) -> Result<String, SetupError> {
+    match std::fs::read_to_string(path) {
+        Ok(words) => Ok(words),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(SetupError::Io(err)),
+    }
+}
+
+/// Appends a word to the custom word list, creating the file if needed.
+pub fn add_custom_word(path: &std::path::Path, word: &str) -> Result<(), std::io::Error> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{word}")
+}
+
+/// Builds the word database from the embedded list using the table generated at compile time, so no analysis runs at startup.
+pub fn embedded_word_db() -> Result<DB, SetupError> {
+    let word_db = DB::new_in_memory()?;
+    let words: Vec<Word> = embedded::EMBEDDED_WORDS
+        .iter()
+        .filter_map(|(word, probability)| Word::new(0, *probability, word).ok())
+        .collect();
+    word_db.batch_insert(&words)?;
+    Ok(word_db)
+}
+
+/// Loads the analysed word database from the cache at `cache_path` when it was built from the same source with the same analyzer version.
+/// Otherwise the source is analysed again and the cache is rewritten. A cache that cannot be written only costs the next launch another analysis.
+pub fn load_word_db(word_src: &str, cache_path: &Path) -> Result<DB, SetupError> {
+    let key = word_cache_key(word_src);
+    if let Ok(Some(db)) = DB::load_word_cache(cache_path, &key) {
+        return Ok(db);
+    }
+
+    let db = setup_word_db(word_src)?;
+    if let Err(err) = db.write_word_cache(cache_path, &key) {
+        eprintln!("Could not write word cache: {err}");
+    }
+    Ok(db)
+}
+
+/// Content hash of the word source combined with the analyzer version, used to tell if a cache is stale.
+pub fn word_cache_key(word_src: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(ANALYZER_VERSION.to_le_bytes());
+    hasher.update(word_src.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
 fn read_words_from_file(
     word_source: &str,
     word_analyzer: &mut WordAnalyzer,
@@ -42,21 +116,103 @@ fn read_words_from_file(
     Ok(())
 }
 
-// pub fn change_word_src(game: &GameLoop) -> Result<(), SetupError> {
-//     let wd = get_working_directory()?;
-//     let word_source = get_new_word_source_path()?;
-//     let full_path = wd.join(&word_source);
-//     print!("Changing word source to {}", full_path.display());
-//     check_full_path(&full_path)?;
-//     let mut word_analyzer = WordAnalyzer::new();
-//     read_words_from_file(&full_path, &mut word_analyzer)?;
-//     // Pop words from the analyzer and add them to the database until there are no more
-//     word_analyzer.finalize_probabilities();
-//     game.db.delete_words()?;
-//     game.db.create_words_table()?;
-//     game.db.batch_insert(word_analyzer.words())?;
-//     Ok(())
-// }
+/// Validates the word list file at `path`, normalising its entries and collecting the lines that cannot be used.
+pub fn validate_word_source(path: &Path) -> Result<ValidationReport, SetupError> {
+    check_full_path(path)?;
+    let word_src = std::fs::read_to_string(path)?;
+    Ok(validate_word_list(&word_src))
+}
+
+/// Analyses the word list at `path` and stores it in the results database under `name`, replacing any source with the same name.
+/// Entries are normalised with `validate_word_list` first and unusable lines are skipped. Returns the number of words stored.
+/// `name` must not be empty or `BUILTIN_SOURCE`, which always selects the built-in list.
+pub fn register_word_source(result_db: &DB, name: &str, path: &Path) -> Result<u32, SetupError> {
+    if name.trim().is_empty() || name == BUILTIN_SOURCE {
+        return Err(SetupError::InvalidSourceName(name.to_string()));
+    }
+    check_full_path(path)?;
+    let word_src = std::fs::read_to_string(path)?;
+    let report = validate_word_list(&word_src);
+    let mut word_analyzer = WordAnalyzer::new();
+    read_words_from_file(&report.cleaned(), &mut word_analyzer)?;
+    word_analyzer.finalize_probabilities();
+    if word_analyzer.get_total_words() == 0 {
+        return Err(SetupError::EmptyWordSource(name.to_string()));
+    }
+
+    let source = WordSource {
+        name: name.to_string(),
+        path: path.display().to_string(),
+        source_hash: word_cache_key(&word_src),
+        word_count: word_analyzer.get_total_words(),
+        added_date: chrono::Utc::now(),
+    };
+    result_db.store_word_source(&source, word_analyzer.words())?;
+    Ok(source.word_count)
+}
+
+/// Loads the word database for the source selected in the config.
+/// With no active source the built-in list is used, together with any custom words.
+pub fn load_active_word_db(config: &Config, result_db: &DB) -> Result<DB, SetupError> {
+    if let Some(name) = &config.active_word_source {
+        return result_db
+            .load_word_source(name)?
+            .ok_or_else(|| SetupError::UnknownWordSource(name.clone()));
+    }
+
+    // The embedded list is analysed at compile time. Custom words change the probabilities, so they need a runtime analysis.
+    let custom_words = load_custom_words(&config.custom_words_path())?;
+    if custom_words.trim().is_empty() {
+        embedded_word_db()
+    } else {
+        load_word_db(
+            &format!("{WORD_LIST}\n{custom_words}"),
+            &config.word_cache_path(),
+        )
+    }
+}
+
+/// Loads the word database like `load_active_word_db` without writing anything: a stale word cache is not rewritten.
+/// With no results database only the built-in list and custom words are available.
+pub fn read_active_word_db(config: &Config, result_db: Option<&DB>) -> Result<DB, SetupError> {
+    if let Some(name) = &config.active_word_source {
+        let source = match result_db {
+            Some(result_db) => result_db.load_word_source(name)?,
+            None => None,
+        };
+        return source.ok_or_else(|| SetupError::UnknownWordSource(name.clone()));
+    }
+
+    let custom_words = load_custom_words(&config.custom_words_path())?;
+    if custom_words.trim().is_empty() {
+        return embedded_word_db();
+    }
+    let word_src = format!("{WORD_LIST}\n{custom_words}");
+    match DB::load_word_cache(&config.word_cache_path(), &word_cache_key(&word_src)) {
+        Ok(Some(db)) => Ok(db),
+        _ => setup_word_db(&word_src),
+    }
+}
+
+/// Hash of the word list selected in the config, the same one used to key the word cache and registered sources.
+/// Replay files record it so a replay against a different list can be flagged.
+pub fn active_word_source_hash(config: &Config, result_db: &DB) -> Result<String, SetupError> {
+    if let Some(name) = &config.active_word_source {
+        return result_db
+            .get_word_sources()?
+            .into_iter()
+            .find(|source| &source.name == name)
+            .map(|source| source.source_hash)
+            .ok_or_else(|| SetupError::UnknownWordSource(name.clone()));
+    }
+
+    let custom_words = load_custom_words(&config.custom_words_path())?;
+    if custom_words.trim().is_empty() {
+        Ok(word_cache_key(WORD_LIST))
+    } else {
+        Ok(word_cache_key(&format!("{WORD_LIST}\n{custom_words}")))
+    }
+}
 
 pub fn get_new_word_source_path() -> Result<String, SetupError> {
     let mut word_source = String::new();
@@ -76,3 +232,150 @@ pub fn check_full_path(full_path: &std::path::Path) -> Result<(), SetupError> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("crackle-cache-{}.db", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_embedded_table_matches_runtime_analysis() {
+        let mut word_analyzer = WordAnalyzer::new();
+        read_words_from_file(crate::constants::WORD_LIST, &mut word_analyzer).unwrap();
+        word_analyzer.finalize_probabilities();
+
+        let runtime: Vec<(String, f64)> = word_analyzer
+            .words()
+            .iter()
+            .map(|w| (w.as_str(), w.total_probability))
+            .collect();
+        let generated: Vec<(String, f64)> = embedded::EMBEDDED_WORDS
+            .iter()
+            .map(|(w, p)| (w.to_string(), *p))
+            .collect();
+        assert_eq!(runtime, generated);
+    }
+
+    #[test]
+    fn test_register_and_load_word_source() {
+        let result_db = DB::new_results_in_memory().unwrap();
+        let path = std::env::temp_dir().join(format!("crackle-words-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "crane\nSLATE\nbad\ncrane\n").unwrap();
+
+        let count = register_word_source(&result_db, "small", &path).unwrap();
+        assert_eq!(count, 2);
+
+        let config = Config {
+            active_word_source: Some("small".to_string()),
+            ..Config::default()
+        };
+        let word_db = load_active_word_db(&config, &result_db).unwrap();
+        assert!(word_db.contains_word("slate").unwrap());
+        assert!(!word_db.contains_word("irate").unwrap());
+
+        let missing = Config {
+            active_word_source: Some("missing".to_string()),
+            ..Config::default()
+        };
+        assert!(matches!(
+            load_active_word_db(&missing, &result_db),
+            Err(SetupError::UnknownWordSource(_))
+        ));
+
+        for name in ["", "  ", BUILTIN_SOURCE] {
+            assert!(matches!(
+                register_word_source(&result_db, name, &path),
+                Err(SetupError::InvalidSourceName(_))
+            ));
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_active_word_db_writes_nothing() {
+        let dir = std::env::temp_dir().join(format!("crackle-read-only-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            app_db: dir.join("crackle.db"),
+            ..Config::default()
+        };
+        std::fs::write(config.custom_words_path(), "xyzzy\n").unwrap();
+
+        let result_db = DB::open_read_only(&config).unwrap();
+        assert!(result_db.is_none());
+        let word_db = read_active_word_db(&config, result_db.as_ref()).unwrap();
+        assert!(word_db.contains_word("xyzzy").unwrap());
+        assert!(!config.app_db.exists());
+        assert!(!config.word_cache_path().exists());
+
+        let named = Config {
+            active_word_source: Some("small".to_string()),
+            ..Config::default()
+        };
+        assert!(matches!(
+            read_active_word_db(&named, None),
+            Err(SetupError::UnknownWordSource(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_word_cache_key_changes_with_source() {
+        assert_eq!(word_cache_key("crane\n"), word_cache_key("crane\n"));
+        assert_ne!(word_cache_key("crane\n"), word_cache_key("slate\n"));
+    }
+
+    #[test]
+    fn test_load_word_db_writes_and_reuses_cache() {
+        let path = temp_cache_path();
+        let source = "crane\nslate\nplate\n";
+
+        let built = load_word_db(source, &path).unwrap();
+        assert!(path.exists());
+
+        let cached = DB::load_word_cache(&path, &word_cache_key(source))
+            .unwrap()
+            .expect("cache should match the source it was built from");
+        assert_eq!(
+            cached
+                .get_top_words(10)
+                .unwrap()
+                .iter()
+                .map(|w| (w.as_str(), w.total_probability))
+                .collect::<Vec<_>>(),
+            built
+                .get_top_words(10)
+                .unwrap()
+                .iter()
+                .map(|w| (w.as_str(), w.total_probability))
+                .collect::<Vec<_>>()
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_word_db_rebuilds_stale_cache() {
+        let path = temp_cache_path();
+        load_word_db("crane\nslate\n", &path).unwrap();
+
+        let rebuilt = load_word_db("crane\nslate\nirate\n", &path).unwrap();
+        assert!(rebuilt.contains_word("irate").unwrap());
+        assert!(
+            DB::load_word_cache(&path, &word_cache_key("crane\nslate\n"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            DB::load_word_cache(&path, &word_cache_key("crane\nslate\nirate\n"))
+                .unwrap()
+                .is_some()
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
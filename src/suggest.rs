@@ -0,0 +1,185 @@
+use crate::DB;
+use crate::config::{Config, PastAnswerMode};
+use crate::constants::WORD_LENGTH;
+use crate::error::{FatalError, RecoverableError};
+use crate::game_engine::GameEngine;
+use crate::input::check_input;
+use crate::session::GuessRecord;
+use serde_json::json;
+
+/// Number of remaining candidates listed when no limit is given.
+pub const DEFAULT_CANDIDATE_LIMIT: usize = 20;
+
+/// The next guess for a game played somewhere else, rebuilt from its guess and feedback history.
+#[derive(Debug, PartialEq)]
+pub struct Suggestion {
+    /// `None` when the game is already solved or no word in the list matches the feedback.
+    pub guess: Option<String>,
+    /// Words still consistent with the feedback and their probabilities, most probable first.
+    pub candidates: Vec<(String, f64)>,
+    pub solved: bool,
+}
+
+impl Suggestion {
+    /// The suggestion with at most `limit` candidates. `remaining` counts every candidate.
+    pub fn to_json(&self, limit: usize) -> serde_json::Value {
+        let candidates: Vec<serde_json::Value> = self
+            .candidates
+            .iter()
+            .take(limit)
+            .map(|(word, probability)| json!({ "word": word, "probability": probability }))
+            .collect();
+        json!({
+            "solved": self.solved,
+            "guess": self.guess,
+            "remaining": self.candidates.len(),
+            "candidates": candidates,
+        })
+    }
+}
+
+/// Parses a history written as whitespace separated `guess:feedback` pairs, for example `crane:nnygy slate:ngnny`.
+pub fn parse_history(src: &str) -> Result<Vec<GuessRecord>, RecoverableError> {
+    src.split_whitespace()
+        .map(|pair| {
+            let (guess, feedback) = pair.split_once(':').ok_or_else(|| {
+                RecoverableError::InputError(format!(
+                    "'{pair}' should be written as guess:feedback"
+                ))
+            })?;
+            let guess = guess.to_lowercase();
+            let feedback = feedback.to_lowercase();
+            if guess.len() != WORD_LENGTH {
+                return Err(RecoverableError::InvalidWordLength(guess.len()));
+            }
+            if !guess.chars().all(|c| c.is_ascii_lowercase()) {
+                return Err(RecoverableError::InputError(format!(
+                    "'{guess}' must only contain letters"
+                )));
+            }
+            check_input(&feedback)?;
+            Ok(GuessRecord { guess, feedback })
+        })
+        .collect()
+}
+
+/// Replays the history into a fresh `GameEngine` and picks the next guess from `word_db`.
+/// Nothing is written to either database, so it is safe to call for games that are not tracked as sessions.
+pub fn suggest(
+    history: &[GuessRecord],
+    word_db: &DB,
+    config: &Config,
+    past_answers: std::collections::HashSet<String>,
+) -> Result<Suggestion, FatalError> {
+    let mut engine = GameEngine::from_history(
+        history
+            .iter()
+            .map(|record| (record.guess.as_str(), record.feedback.as_str())),
+    );
+    if engine.check_for_win() {
+        return Ok(Suggestion {
+            guess: None,
+            candidates: Vec::new(),
+            solved: true,
+        });
+    }
+    if config.past_answer_mode != PastAnswerMode::Ignore {
+        engine.set_past_answers(
+            past_answers,
+            config.past_answer_mode,
+            config.past_answer_weight,
+        );
+    }
+
+    let possible_words = word_db.filter_words(&engine.get_pattern())?;
+    let candidates = engine
+        .rank_candidates(possible_words.clone())
+        .iter()
+        .map(|word| (word.as_str(), word.total_probability))
+        .collect();
+    Ok(Suggestion {
+        guess: engine.get_next_guess(possible_words).ok(),
+        candidates,
+        solved: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word_analyzer::Word;
+
+    fn word_db(words: &[&str]) -> DB {
+        let db = DB::new_in_memory().unwrap();
+        for word in words {
+            db.add_word(Word::new(0, 0.0, word).unwrap()).unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn test_parse_history() {
+        let history = parse_history("CRANE:nnygy\n slate:NGNNY").unwrap();
+        assert_eq!(
+            history,
+            vec![
+                GuessRecord {
+                    guess: "crane".to_string(),
+                    feedback: "nnygy".to_string(),
+                },
+                GuessRecord {
+                    guess: "slate".to_string(),
+                    feedback: "ngnny".to_string(),
+                },
+            ]
+        );
+        assert!(parse_history("").unwrap().is_empty());
+        assert!(parse_history("crane").is_err());
+        assert!(parse_history("crane:nnygx").is_err());
+        assert!(parse_history("cran:nnygy").is_err());
+    }
+
+    #[test]
+    fn test_suggest_narrows_candidates() {
+        let db = word_db(&["crane", "slate", "plate", "elate", "irate"]);
+        let history = parse_history("slate:nnggg").unwrap();
+        let suggestion = suggest(&history, &db, &Config::default(), Default::default()).unwrap();
+
+        let words: Vec<&str> = suggestion
+            .candidates
+            .iter()
+            .map(|(word, _)| word.as_str())
+            .collect();
+        assert_eq!(words, vec!["irate"]);
+        assert_eq!(suggestion.guess.as_deref(), Some("irate"));
+        assert!(!suggestion.solved);
+
+        let json = suggestion.to_json(0);
+        assert_eq!(json["guess"], "irate");
+        assert_eq!(json["remaining"], 1);
+        assert_eq!(json["candidates"], json!([]));
+    }
+
+    #[test]
+    fn test_suggest_solved_and_stumped() {
+        let db = word_db(&["crane", "slate"]);
+        let solved = suggest(
+            &parse_history("crane:ggggg").unwrap(),
+            &db,
+            &Config::default(),
+            Default::default(),
+        )
+        .unwrap();
+        assert!(solved.solved);
+
+        let stumped = suggest(
+            &parse_history("crane:yyyyy").unwrap(),
+            &db,
+            &Config::default(),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(stumped.guess, None);
+        assert!(stumped.candidates.is_empty());
+    }
+}
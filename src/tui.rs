@@ -0,0 +1,488 @@
+use crate::constants::WORD_LENGTH;
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use std::collections::HashMap;
+
+/// Rows of the on-screen keyboard.
+const KEYBOARD_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Colour of one tile. Ordered so the best known state of a letter is the maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TileState {
+    Absent,
+    Present,
+    Correct,
+}
+
+impl TileState {
+    /// The next colour when cycling a tile: grey, yellow, green and back to grey.
+    pub fn next(self) -> Self {
+        match self {
+            TileState::Absent => TileState::Present,
+            TileState::Present => TileState::Correct,
+            TileState::Correct => TileState::Absent,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        self.next().next()
+    }
+
+    /// The `gyn` feedback character for the tile.
+    pub fn as_char(self) -> char {
+        match self {
+            TileState::Absent => 'n',
+            TileState::Present => 'y',
+            TileState::Correct => 'g',
+        }
+    }
+
+    fn style(self) -> Style {
+        let background = match self {
+            TileState::Absent => Color::DarkGray,
+            TileState::Present => Color::Yellow,
+            TileState::Correct => Color::Green,
+        };
+        Style::new()
+            .bg(background)
+            .fg(Color::Black)
+            .add_modifier(Modifier::BOLD)
+    }
+}
+
+/// A guess and the colours of its tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub guess: String,
+    pub tiles: [TileState; WORD_LENGTH],
+}
+
+impl Row {
+    pub fn feedback(&self) -> String {
+        self.tiles.iter().map(|tile| tile.as_char()).collect()
+    }
+}
+
+/// What a key press did while feedback is being entered.
+#[derive(Debug, PartialEq)]
+pub enum KeyOutcome {
+    Continue,
+    /// The row was submitted with this feedback.
+    Submit(String),
+    Quit,
+}
+
+/// Everything shown on screen, kept apart from the terminal so it can be tested.
+#[derive(Debug)]
+pub struct Board {
+    pub rows: Vec<Row>,
+    /// The guess waiting for feedback, if any.
+    pub current: Option<Row>,
+    pub cursor: usize,
+    /// `None` until the first feedback has narrowed down the word list.
+    pub candidates: Option<Vec<(String, f64)>>,
+    pub scroll: usize,
+    pub status: String,
+    pub max_guesses: u8,
+}
+
+impl Board {
+    pub fn new(max_guesses: u8) -> Self {
+        Board {
+            rows: Vec::new(),
+            current: None,
+            cursor: 0,
+            candidates: None,
+            scroll: 0,
+            status: String::new(),
+            max_guesses,
+        }
+    }
+
+    /// Starts a new row for `guess` with every tile grey.
+    pub fn set_guess(&mut self, guess: &str) {
+        self.current = Some(Row {
+            guess: guess.to_string(),
+            tiles: [TileState::Absent; WORD_LENGTH],
+        });
+        self.cursor = 0;
+    }
+
+    pub fn set_candidates(&mut self, candidates: Vec<(String, f64)>) {
+        self.candidates = Some(candidates);
+        self.scroll = 0;
+    }
+
+    /// Handles a key press on the row being edited.
+    pub fn handle_key(&mut self, key: KeyCode) -> KeyOutcome {
+        let Some(row) = self.current.as_mut() else {
+            return KeyOutcome::Continue;
+        };
+        match key {
+            KeyCode::Esc | KeyCode::Char('q') => return KeyOutcome::Quit,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.cursor = (self.cursor + 1).min(WORD_LENGTH - 1)
+            }
+            KeyCode::Char(' ') | KeyCode::Up => {
+                row.tiles[self.cursor] = row.tiles[self.cursor].next()
+            }
+            KeyCode::Down => row.tiles[self.cursor] = row.tiles[self.cursor].previous(),
+            KeyCode::Char(c @ ('g' | 'y' | 'n')) => {
+                row.tiles[self.cursor] = match c {
+                    'g' => TileState::Correct,
+                    'y' => TileState::Present,
+                    _ => TileState::Absent,
+                };
+                self.cursor = (self.cursor + 1).min(WORD_LENGTH - 1);
+            }
+            KeyCode::PageDown | KeyCode::Char('j') => self.scroll_candidates(1),
+            KeyCode::PageUp | KeyCode::Char('k') => self.scroll_candidates(-1),
+            KeyCode::Enter => {
+                let feedback = row.feedback();
+                self.rows.extend(self.current.take());
+                self.status.clear();
+                return KeyOutcome::Submit(feedback);
+            }
+            _ => {}
+        }
+        KeyOutcome::Continue
+    }
+
+    fn scroll_candidates(&mut self, delta: isize) {
+        let len = self.candidates.as_ref().map_or(0, Vec::len);
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    /// The best state seen for every letter in submitted rows.
+    pub fn letter_states(&self) -> HashMap<char, TileState> {
+        let mut states = HashMap::new();
+        for row in &self.rows {
+            for (c, tile) in row.guess.chars().zip(row.tiles) {
+                states
+                    .entry(c)
+                    .and_modify(|state: &mut TileState| *state = (*state).max(tile))
+                    .or_insert(tile);
+            }
+        }
+        states
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [title, main, status, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(10),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Length(46), Constraint::Min(20)]).areas(main);
+        let [board, keyboard] = Layout::vertical([
+            Constraint::Length(self.max_guesses as u16 * 2 + 2),
+            Constraint::Length(5),
+        ])
+        .areas(left);
+
+        frame.render_widget(
+            Paragraph::new(Line::styled(
+                "Crackle",
+                Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            )),
+            title,
+        );
+        frame.render_widget(self.board_widget(), board);
+        frame.render_widget(self.keyboard_widget(), keyboard);
+        self.render_candidates(frame, right);
+        frame.render_widget(
+            Paragraph::new(self.status.as_str())
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered()),
+            status,
+        );
+        frame.render_widget(
+            Paragraph::new(
+                "←/→ move  space/↑/↓ cycle colour  g/y/n set tile  enter submit  j/k scroll  esc quit",
+            )
+            .style(Style::new().fg(Color::DarkGray)),
+            help,
+        );
+    }
+
+    fn board_widget(&self) -> Paragraph<'_> {
+        let mut lines = Vec::new();
+        for row in &self.rows {
+            lines.push(tile_line(row, None));
+            lines.push(Line::default());
+        }
+        if let Some(row) = &self.current {
+            lines.push(tile_line(row, Some(self.cursor)));
+            lines.push(Line::default());
+        }
+        let empty = Style::new().bg(Color::Black);
+        while lines.len() < self.max_guesses as usize * 2 {
+            let spans: Vec<Span> = (0..WORD_LENGTH)
+                .flat_map(|_| [Span::styled("   ", empty), Span::raw(" ")])
+                .collect();
+            lines.push(Line::from(spans));
+            lines.push(Line::default());
+        }
+        Paragraph::new(lines).block(Block::bordered().title("Board"))
+    }
+
+    fn keyboard_widget(&self) -> Paragraph<'_> {
+        let states = self.letter_states();
+        let lines: Vec<Line> = KEYBOARD_ROWS
+            .iter()
+            .enumerate()
+            .map(|(indent, keys)| {
+                let mut spans = vec![Span::raw(" ".repeat(indent * 2))];
+                for c in keys.chars() {
+                    let style = states
+                        .get(&c)
+                        .map_or(Style::new().fg(Color::White), |state| state.style());
+                    spans.push(Span::styled(format!(" {} ", c.to_ascii_uppercase()), style));
+                    spans.push(Span::raw(" "));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        Paragraph::new(lines).block(Block::bordered().title("Keyboard"))
+    }
+
+    fn render_candidates(&self, frame: &mut Frame, area: Rect) {
+        let (title, lines) = match &self.candidates {
+            None => ("Candidates: whole word list".to_string(), Vec::new()),
+            Some(candidates) => (
+                format!("Candidates: {}", candidates.len()),
+                candidates
+                    .iter()
+                    .map(|(word, probability)| Line::from(format!("{word}  {probability:.4}")))
+                    .collect(),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(lines)
+                .scroll((self.scroll as u16, 0))
+                .block(Block::bordered().title(title)),
+            area,
+        );
+    }
+}
+
+fn tile_line(row: &Row, cursor: Option<usize>) -> Line<'_> {
+    let spans: Vec<Span> = row
+        .guess
+        .chars()
+        .zip(row.tiles)
+        .enumerate()
+        .flat_map(|(i, (c, tile))| {
+            let mut style = tile.style();
+            if cursor == Some(i) {
+                style = style.add_modifier(Modifier::UNDERLINED | Modifier::REVERSED);
+            }
+            [
+                Span::styled(format!(" {} ", c.to_ascii_uppercase()), style),
+                Span::raw(" "),
+            ]
+        })
+        .collect();
+    Line::from(spans)
+}
+
+/// The full-screen terminal, shared by `TuiInput` and `TuiOutput`. The terminal is restored when it is dropped.
+pub struct Tui {
+    terminal: DefaultTerminal,
+    pub board: Board,
+}
+
+impl Tui {
+    /// Switches the terminal to the alternate screen and raw mode.
+    pub fn new(max_guesses: u8) -> std::io::Result<Self> {
+        Ok(Tui {
+            terminal: ratatui::try_init()?,
+            board: Board::new(max_guesses),
+        })
+    }
+
+    pub fn draw(&mut self) -> std::io::Result<()> {
+        let board = &self.board;
+        self.terminal.draw(|frame| board.render(frame))?;
+        Ok(())
+    }
+
+    /// Waits for the next key press, ignoring releases and other events.
+    fn next_key(&mut self) -> std::io::Result<KeyCode> {
+        loop {
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                return Ok(key.code);
+            }
+        }
+    }
+
+    /// Lets the player colour the tiles of the current guess until they submit it.
+    /// Quitting returns `None`, the same as typing `exit` in a line-based session.
+    pub fn read_feedback(&mut self) -> std::io::Result<Option<String>> {
+        loop {
+            self.draw()?;
+            let key = self.next_key()?;
+            match self.board.handle_key(key) {
+                KeyOutcome::Continue => {}
+                KeyOutcome::Submit(feedback) => return Ok(Some(feedback)),
+                KeyOutcome::Quit => return Ok(None),
+            }
+        }
+    }
+
+    /// Asks for the answer in the status box. Escape or an empty answer skips it.
+    pub fn read_answer(&mut self) -> std::io::Result<Option<String>> {
+        let mut answer = String::new();
+        loop {
+            self.board.status = format!(
+                "What was the answer? {}_  (enter to confirm, esc to skip)",
+                answer.to_uppercase()
+            );
+            self.draw()?;
+            match self.next_key()? {
+                KeyCode::Char(c) if c.is_ascii_alphabetic() && answer.len() < WORD_LENGTH => {
+                    answer.push(c.to_ascii_lowercase())
+                }
+                KeyCode::Backspace => {
+                    answer.pop();
+                }
+                KeyCode::Enter => return Ok((!answer.is_empty()).then_some(answer)),
+                KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn confirm(&mut self, question: &str) -> std::io::Result<bool> {
+        self.board.status = format!("{question} (y/n)");
+        loop {
+            self.draw()?;
+            match self.next_key()? {
+                KeyCode::Char('y') => return Ok(true),
+                KeyCode::Char('n') | KeyCode::Esc => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+
+    /// Shows a final message and waits for any key before the screen is closed.
+    pub fn finish(&mut self, message: &str) -> std::io::Result<()> {
+        self.board.current = None;
+        self.board.status = format!("{message}  Press any key to continue.");
+        self.draw()?;
+        self.next_key()?;
+        Ok(())
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles_cycle() {
+        assert_eq!(TileState::Absent.next(), TileState::Present);
+        assert_eq!(TileState::Correct.next(), TileState::Absent);
+        assert_eq!(TileState::Absent.previous(), TileState::Correct);
+    }
+
+    #[test]
+    fn test_keys_edit_and_submit_row() {
+        let mut board = Board::new(6);
+        board.set_guess("crane");
+
+        board.handle_key(KeyCode::Char(' '));
+        board.handle_key(KeyCode::Right);
+        board.handle_key(KeyCode::Char('g'));
+        board.handle_key(KeyCode::Down);
+        board.handle_key(KeyCode::Right);
+        board.handle_key(KeyCode::Right);
+        board.handle_key(KeyCode::Right);
+        assert_eq!(board.cursor, WORD_LENGTH - 1);
+
+        assert_eq!(
+            board.handle_key(KeyCode::Enter),
+            KeyOutcome::Submit("yggnn".to_string())
+        );
+        assert!(board.current.is_none());
+        assert_eq!(board.rows[0].feedback(), "yggnn");
+        assert_eq!(board.handle_key(KeyCode::Enter), KeyOutcome::Continue);
+
+        board.set_guess("slate");
+        assert_eq!(board.handle_key(KeyCode::Esc), KeyOutcome::Quit);
+    }
+
+    #[test]
+    fn test_letter_states_keep_best() {
+        let mut board = Board::new(6);
+        board.set_guess("eerie");
+        board.handle_key(KeyCode::Char('g'));
+        board.handle_key(KeyCode::Enter);
+
+        let states = board.letter_states();
+        assert_eq!(states[&'e'], TileState::Correct);
+        assert_eq!(states[&'r'], TileState::Absent);
+        assert!(!states.contains_key(&'z'));
+    }
+
+    #[test]
+    fn test_candidate_scroll_is_clamped() {
+        let mut board = Board::new(6);
+        board.set_guess("crane");
+        board.handle_key(KeyCode::PageDown);
+        assert_eq!(board.scroll, 0);
+
+        board.set_candidates(vec![("slate".to_string(), 0.6), ("plate".to_string(), 0.4)]);
+        board.handle_key(KeyCode::PageDown);
+        board.handle_key(KeyCode::PageDown);
+        assert_eq!(board.scroll, 1);
+        board.handle_key(KeyCode::PageUp);
+        board.handle_key(KeyCode::PageUp);
+        assert_eq!(board.scroll, 0);
+    }
+
+    #[test]
+    fn test_render_shows_candidates_and_keyboard() {
+        let mut board = Board::new(6);
+        board.set_guess("crane");
+        board.handle_key(KeyCode::Char('g'));
+        board.handle_key(KeyCode::Enter);
+        board.set_candidates(vec![("cloud".to_string(), 0.6), ("civic".to_string(), 0.4)]);
+        board.set_guess("cloud");
+
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| board.render(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        assert!(screen.contains("Candidates: 2"));
+        assert!(screen.contains("civic  0.4000"));
+        assert!(screen.contains(" C   R   A   N   E "));
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use thiserror::Error;
 
+/// Version of the scoring model. Bump this whenever the way probabilities are calculated changes so cached word databases are rebuilt.
+pub const ANALYZER_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Character {
     pub character: u8,
@@ -194,10 +197,10 @@ impl WordAnalyzer {
             let mut word_probability = 0u32;
             for (i, character) in word.word.iter().enumerate() {
                 let key = format!("{}{}", character.get_char(), i);
-                if let Some(char_data) = self.character_hash_map.get(&key) {
-                    if let Some(prob) = char_data.probability {
-                        word_probability += prob;
-                    }
+                if let Some(char_data) = self.character_hash_map.get(&key)
+                    && let Some(prob) = char_data.probability
+                {
+                    word_probability += prob;
                 }
             }
             word.update_probability(word_probability as f64 / 100.0);
@@ -0,0 +1,252 @@
+use crate::constants::WORD_LENGTH;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Marks the start of a comment in a word list. Everything after it on a line is ignored.
+const COMMENT_MARKER: char = '#';
+
+/// Why a line of a word list was dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    WrongLength(usize),
+    NonAlphabetic(char),
+    Duplicate {
+        first_line: usize,
+    },
+    /// The line does not have the expected shape, with a short description of what was expected.
+    Malformed(&'static str),
+}
+
+/// A dropped line, with its 1-based line number and the entry after normalisation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineIssue {
+    pub line: usize,
+    pub entry: String,
+    pub kind: IssueKind,
+}
+
+impl Display for LineIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            IssueKind::WrongLength(len) => write!(
+                f,
+                "line {}: '{}' has {len} characters, expected {WORD_LENGTH}",
+                self.line, self.entry
+            ),
+            IssueKind::NonAlphabetic(c) => write!(
+                f,
+                "line {}: '{}' contains non-alphabetic character '{c}'",
+                self.line, self.entry
+            ),
+            IssueKind::Duplicate { first_line } => write!(
+                f,
+                "line {}: '{}' duplicates line {first_line}",
+                self.line, self.entry
+            ),
+            IssueKind::Malformed(expected) => {
+                write!(f, "line {}: '{}' {expected}", self.line, self.entry)
+            }
+        }
+    }
+}
+
+/// The result of validating a word list: the cleaned words in their original order and every line that was dropped.
+#[derive(Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    pub words: Vec<String>,
+    pub issues: Vec<LineIssue>,
+    /// Lines that were blank or only held a comment.
+    pub skipped_lines: usize,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The cleaned list with one word per line, ready to be written back to a file.
+    pub fn cleaned(&self) -> String {
+        let mut cleaned = self.words.join("\n");
+        if !cleaned.is_empty() {
+            cleaned.push('\n');
+        }
+        cleaned
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} valid words, {} problem lines, {} blank or comment lines",
+            self.words.len(),
+            self.issues.len(),
+            self.skipped_lines
+        )?;
+        for issue in &self.issues {
+            writeln!(f, "  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Why one field of a line cannot be used: the entry as read and the issue with it.
+pub type FieldError = (String, IssueKind);
+
+/// Strips a `#` comment and surrounding whitespace from a line. Returns `None` when nothing is left.
+pub fn strip_comment(line: &str) -> Option<&str> {
+    let line = line.split(COMMENT_MARKER).next().unwrap_or_default().trim();
+    (!line.is_empty()).then_some(line)
+}
+
+/// Lowercases `entry` and checks that it is a word of `WORD_LENGTH` letters.
+pub fn check_word(entry: &str) -> Result<String, FieldError> {
+    let word = entry.to_lowercase();
+    if let Some(c) = word.chars().find(|c| !c.is_ascii_alphabetic()) {
+        Err((word, IssueKind::NonAlphabetic(c)))
+    } else if word.len() != WORD_LENGTH {
+        let len = word.chars().count();
+        Err((word, IssueKind::WrongLength(len)))
+    } else {
+        Ok(word)
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date field.
+pub fn parse_date(field: &str) -> Result<NaiveDate, FieldError> {
+    NaiveDate::parse_from_str(field, "%Y-%m-%d").map_err(|_| {
+        (
+            field.to_string(),
+            IssueKind::Malformed("is not a YYYY-MM-DD date"),
+        )
+    })
+}
+
+/// Reads a list whose lines hold fields separated by commas or whitespace, such as the answer archive.
+/// Blank lines and `#` comments are skipped, and `parse` turns the fields of every other line into an entry or the reason it was dropped.
+pub fn parse_fields<T>(
+    src: &str,
+    mut parse: impl FnMut(&str, &[&str]) -> Result<T, FieldError>,
+) -> (Vec<T>, Vec<LineIssue>) {
+    let mut entries = Vec::new();
+    let mut issues = Vec::new();
+    for (idx, line) in src.lines().enumerate() {
+        let Some(line) = strip_comment(line) else {
+            continue;
+        };
+        let fields: Vec<&str> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|field| !field.is_empty())
+            .collect();
+        match parse(line, &fields) {
+            Ok(entry) => entries.push(entry),
+            Err((entry, kind)) => issues.push(LineIssue {
+                line: idx + 1,
+                entry,
+                kind,
+            }),
+        }
+    }
+    (entries, issues)
+}
+
+/// Normalises a word list and reports the lines that cannot be used.
+/// Entries are lowercased, surrounding whitespace and `#` comments are stripped, and repeated words are dropped.
+pub fn validate_word_list(word_src: &str) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+
+    for (idx, line) in word_src.lines().enumerate() {
+        let line_number = idx + 1;
+        let Some(entry) = strip_comment(line) else {
+            report.skipped_lines += 1;
+            continue;
+        };
+
+        let checked = check_word(entry).and_then(|word| match first_seen.get(&word) {
+            Some(&first_line) => Err((word, IssueKind::Duplicate { first_line })),
+            None => Ok(word),
+        });
+        match checked {
+            Ok(word) => {
+                first_seen.insert(word.clone(), line_number);
+                report.words.push(word);
+            }
+            Err((entry, kind)) => report.issues.push(LineIssue {
+                line: line_number,
+                entry,
+                kind,
+            }),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_normalises_entries() {
+        let report = validate_word_list("  CRANE \n# header comment\n\nslate # common opener\n");
+        assert_eq!(report.words, vec!["crane", "slate"]);
+        assert_eq!(report.skipped_lines, 2);
+        assert!(report.is_clean());
+        assert_eq!(report.cleaned(), "crane\nslate\n");
+    }
+
+    #[test]
+    fn test_validate_reports_issues_with_line_numbers() {
+        let report = validate_word_list("crane\ncran\nsl@te\ncrane\nirate\n");
+        assert_eq!(report.words, vec!["crane", "irate"]);
+        assert_eq!(
+            report.issues,
+            vec![
+                LineIssue {
+                    line: 2,
+                    entry: "cran".to_string(),
+                    kind: IssueKind::WrongLength(4),
+                },
+                LineIssue {
+                    line: 3,
+                    entry: "sl@te".to_string(),
+                    kind: IssueKind::NonAlphabetic('@'),
+                },
+                LineIssue {
+                    line: 4,
+                    entry: "crane".to_string(),
+                    kind: IssueKind::Duplicate { first_line: 1 },
+                },
+            ]
+        );
+        assert!(report.to_string().contains("line 3: 'sl@te'"));
+    }
+
+    #[test]
+    fn test_parse_fields_reports_rejected_lines() {
+        let (dates, issues) = parse_fields(
+            "# dates\n2021-06-19, cigar\n\n2021-13-01 rebut\n",
+            |_, fields| parse_date(fields[0]),
+        );
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2021, 6, 19).unwrap()]);
+        assert_eq!(
+            issues,
+            vec![LineIssue {
+                line: 4,
+                entry: "2021-13-01".to_string(),
+                kind: IssueKind::Malformed("is not a YYYY-MM-DD date"),
+            }]
+        );
+        assert_eq!(check_word("Crane"), Ok("crane".to_string()));
+    }
+
+    #[test]
+    fn test_validate_non_ascii_is_non_alphabetic() {
+        let report = validate_word_list("café!\n");
+        assert!(matches!(
+            report.issues[0].kind,
+            IssueKind::NonAlphabetic('é')
+        ));
+    }
+}